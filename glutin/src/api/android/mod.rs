@@ -1,12 +1,18 @@
 #![cfg(target_os = "android")]
 
-use crate::api::egl::{Context as EglContext, NativeDisplay, SurfaceType as EglSurfaceType};
+use crate::api::egl::{
+    Context as EglContext, EglDisplay, NativeDisplay, SurfaceType as EglSurfaceType,
+};
 use crate::CreationError::{self, OsError};
-use crate::{Api, ContextError, GlAttributes, PixelFormat, PixelFormatRequirements, Rect};
+use crate::{
+    Api, ContextError, ContextPriority, GlAttributes, PixelFormat, PixelFormatRequirements, Rect,
+    RendererInfo,
+};
 
 use crate::platform::android::EventLoopExtAndroid;
 use glutin_egl_sys as ffi;
 use parking_lot::Mutex;
+use raw_window_handle::RawWindowHandle;
 use winit;
 use winit::dpi;
 use winit::event_loop::EventLoopWindowTarget;
@@ -95,6 +101,40 @@ impl Context {
         Ok((win, context))
     }
 
+    #[inline]
+    pub unsafe fn new_from_raw_window_handle(
+        handle: RawWindowHandle,
+        _size: dpi::PhysicalSize<u32>,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Self>,
+    ) -> Result<Self, CreationError> {
+        let nwin = match handle {
+            RawWindowHandle::AndroidNdk(handle) if !handle.a_native_window.is_null() => {
+                handle.a_native_window
+            }
+            RawWindowHandle::AndroidNdk(_) => {
+                return Err(OsError("Android's native window is null".to_string()));
+            }
+            _ => {
+                return Err(CreationError::NotSupported(
+                    "only native window handles are supported on Android".to_string(),
+                ));
+            }
+        };
+        let gl_attr = gl_attr.clone().map_sharing(|c| &c.0.egl_context);
+        let egl_context = EglContext::new(
+            pf_reqs,
+            &gl_attr,
+            NativeDisplay::Android,
+            EglSurfaceType::Window,
+            |c, _| Ok(c[0]),
+        )
+        .and_then(|p| p.finish(nwin as *const _))?;
+        // the owner of the window is in charge of its lifecycle
+        let ctx = Arc::new(AndroidContext { egl_context, stopped: None });
+        Ok(Context(ctx))
+    }
+
     #[inline]
     pub fn new_headless<T>(
         _el: &EventLoopWindowTarget<T>,
@@ -140,7 +180,9 @@ impl Context {
     }
 
     #[inline]
-    pub fn resize(&self, _: u32, _: u32) {}
+    pub fn resize(&self, _: u32, _: u32) -> Result<(), ContextError> {
+        Ok(())
+    }
 
     #[inline]
     pub fn is_current(&self) -> bool {
@@ -179,11 +221,52 @@ impl Context {
         self.0.egl_context.swap_buffers_with_damage_supported()
     }
 
+    #[inline]
+    pub fn set_damage_region(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        if let Some(ref stopped) = self.0.stopped {
+            let stopped = stopped.lock();
+            if *stopped {
+                return Err(ContextError::ContextLost);
+            }
+        }
+        self.0.egl_context.set_damage_region(rects)
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
+        self.0.egl_context.set_damage_region_supported()
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        self.0.egl_context.buffer_age()
+    }
+
     #[inline]
     pub fn get_api(&self) -> Api {
         self.0.egl_context.get_api()
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        self.0.egl_context.get_api_version()
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        self.0.egl_context.platform_extensions()
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        self.0.egl_context.priority()
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        self.0.egl_context.renderer_info()
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         self.0.egl_context.get_pixel_format()
@@ -198,4 +281,9 @@ impl Context {
     pub unsafe fn get_egl_display(&self) -> ffi::EGLDisplay {
         self.0.egl_context.get_egl_display()
     }
+
+    #[inline]
+    pub fn egl_display(&self) -> Option<EglDisplay> {
+        Some(self.0.egl_context.egl_display().clone())
+    }
 }
@@ -15,6 +15,7 @@ use libloading::os::windows;
 #[cfg(target_os = "windows")]
 use winapi::um::libloaderapi::*;
 
+use std::ffi::{c_void, CString};
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
 
@@ -30,26 +31,52 @@ pub trait SymTrait {
 
 impl<T: SymTrait> SymWrapper<T> {
     pub fn new(lib_paths: Vec<&str>) -> Result<Self, ()> {
-        for path in lib_paths {
-            // Avoid loading from PATH
-            #[cfg(target_os = "windows")]
-            let lib = unsafe {
-                windows::Library::load_with_flags(path, LOAD_LIBRARY_SEARCH_DEFAULT_DIRS)
-                    .map(From::from)
-            };
-
-            #[cfg(not(target_os = "windows"))]
-            let lib = unsafe { Library::new(path) };
-
-            if lib.is_ok() {
-                return Ok(SymWrapper {
-                    inner: T::load_with(lib.as_ref().unwrap()),
-                    _lib: Arc::new(lib.unwrap()),
-                });
-            }
+        let lib = open_library(&lib_paths).ok_or(())?;
+        Ok(SymWrapper { inner: T::load_with(&lib), _lib: Arc::new(lib) })
+    }
+}
+
+impl<T> SymWrapper<T> {
+    /// Looks a symbol up in the library itself, e.g. a core GL function its
+    /// `GetProcAddress` doesn't return.
+    #[cfg_attr(not(feature = "x11"), allow(dead_code))]
+    pub fn get_symbol(&self, name: &str) -> *const c_void {
+        get_symbol(&self._lib, name)
+    }
+}
+
+/// Opens the first library of `lib_paths` that can be loaded.
+pub fn open_library(lib_paths: &[&str]) -> Option<Library> {
+    for path in lib_paths {
+        // Avoid loading from PATH
+        #[cfg(target_os = "windows")]
+        let lib = unsafe {
+            windows::Library::load_with_flags(path, LOAD_LIBRARY_SEARCH_DEFAULT_DIRS)
+                .map(From::from)
+        };
+
+        #[cfg(not(target_os = "windows"))]
+        let lib = unsafe { Library::new(path) };
+
+        if lib.is_ok() {
+            return lib.ok();
         }
+    }
+
+    None
+}
 
-        Err(())
+/// Looks a symbol up with `dlsym`, or `GetProcAddress` on Windows, returning
+/// null if it's missing.
+pub fn get_symbol(lib: &Library, name: &str) -> *const c_void {
+    let name = match CString::new(name) {
+        Ok(name) => name,
+        Err(_) => return std::ptr::null(),
+    };
+    unsafe {
+        lib.get::<*const c_void>(name.as_bytes_with_nul())
+            .map(|sym| *sym)
+            .unwrap_or(std::ptr::null())
     }
 }
 
@@ -0,0 +1,168 @@
+use super::{get_client_extensions, get_native_display, NativeDisplay, EGL};
+use crate::CreationError;
+
+use glutin_egl_sys as ffi;
+use parking_lot::Mutex;
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+
+lazy_static! {
+    // The displays in use, by handle. EGL doesn't count references itself:
+    // every `eglGetDisplay` call for a native display returns the same
+    // handle, and a single `eglTerminate` terminates it for everyone.
+    static ref DISPLAYS: Mutex<HashMap<usize, Weak<DisplayInner>>> = Mutex::new(HashMap::new());
+}
+
+/// An initialized EGL display, shared by every context created on it.
+///
+/// The display is initialized when the first context using it is built, and
+/// terminated once the last handle to it is dropped, the ones held by its
+/// contexts included. Keeping a handle around, as returned by
+/// [`Context::egl_display`], saves initializing the display again for each
+/// context.
+///
+/// Terminating the display also affects whoever else uses it in the process,
+/// e.g. another library rendering with EGL on the same X11 display. Use
+/// [`set_terminate_on_drop`] to leave it initialized.
+///
+/// Contexts can be built on the display with
+/// [`ContextBuilder::with_egl_display`].
+///
+/// [`Context::egl_display`]: struct.Context.html#method.egl_display
+/// [`set_terminate_on_drop`]: #method.set_terminate_on_drop
+/// [`ContextBuilder::with_egl_display`]: struct.ContextBuilder.html#method.with_egl_display
+#[derive(Debug, Clone)]
+pub struct EglDisplay {
+    inner: Arc<DisplayInner>,
+}
+
+#[derive(Debug)]
+struct DisplayInner {
+    display: ffi::egl::types::EGLDisplay,
+    version: (ffi::egl::types::EGLint, ffi::egl::types::EGLint),
+    extensions: Vec<String>,
+    client_extensions: Vec<String>,
+    terminate_on_drop: AtomicBool,
+}
+
+unsafe impl Send for DisplayInner {}
+unsafe impl Sync for DisplayInner {}
+
+impl EglDisplay {
+    /// Returns the display of `native_display`, initializing it unless it's
+    /// in use already.
+    pub(crate) fn get(native_display: &NativeDisplay) -> Result<Self, CreationError> {
+        // calling `eglGetDisplay` or equivalent
+        let display = get_native_display(native_display)?;
+        if display.is_null() {
+            return Err(CreationError::OsError("Could not create EGL display object".to_string()));
+        }
+
+        // held until the display is registered, so that it can't be
+        // terminated in between
+        let mut displays = DISPLAYS.lock();
+        if let Some(inner) = displays.get(&(display as usize)).and_then(Weak::upgrade) {
+            return Ok(EglDisplay { inner });
+        }
+
+        let egl = EGL.as_ref().unwrap();
+        let mut version = (0, 0);
+        if unsafe { egl.Initialize(display, &mut version.0, &mut version.1) } == 0 {
+            return Err(CreationError::OsError("eglInitialize failed".to_string()));
+        }
+
+        // the list of extensions supported by the client once initialized is
+        // different from the list of extensions obtained earlier
+        let extensions = if version >= (1, 2) {
+            let p =
+                unsafe { CStr::from_ptr(egl.QueryString(display, ffi::egl::EXTENSIONS as i32)) };
+            let list = String::from_utf8(p.to_bytes().to_vec()).unwrap_or_default();
+            list.split(' ').map(|e| e.to_string()).collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+
+        let inner = Arc::new(DisplayInner {
+            display,
+            version,
+            extensions,
+            client_extensions: get_client_extensions(),
+            terminate_on_drop: AtomicBool::new(true),
+        });
+        displays.insert(display as usize, Arc::downgrade(&inner));
+        Ok(EglDisplay { inner })
+    }
+
+    /// Returns the `EGLDisplay`. It stays valid as long as this handle.
+    #[inline]
+    pub fn raw_handle(&self) -> *const raw::c_void {
+        self.inner.display
+    }
+
+    /// Returns the version of EGL, as `(major, minor)`.
+    #[inline]
+    pub fn version(&self) -> (i32, i32) {
+        self.inner.version
+    }
+
+    /// Returns the extensions of the display.
+    #[inline]
+    pub fn extensions(&self) -> &[String] {
+        &self.inner.extensions
+    }
+
+    /// Returns the client extensions, which are supported without any
+    /// display. See [`egl_client_extensions`].
+    ///
+    /// [`egl_client_extensions`]: fn.egl_client_extensions.html
+    #[inline]
+    pub fn client_extensions(&self) -> &[String] {
+        &self.inner.client_extensions
+    }
+
+    /// Returns whether the display supports the given extension.
+    #[inline]
+    pub fn has_extension(&self, extension: &str) -> bool {
+        self.inner.extensions.iter().any(|e| e == extension)
+    }
+
+    /// Sets whether the display is terminated with `eglTerminate` once the
+    /// last handle to it is dropped. Default is `true`.
+    ///
+    /// Set it to `false` if something else in the process uses the display
+    /// without glutin knowing, e.g. another library rendering with EGL. The
+    /// display then stays initialized until the process exits.
+    #[inline]
+    pub fn set_terminate_on_drop(&self, terminate: bool) {
+        self.inner.terminate_on_drop.store(terminate, Ordering::Relaxed);
+    }
+
+    /// Returns whether the display is terminated once the last handle to it
+    /// is dropped. See [`set_terminate_on_drop`].
+    ///
+    /// [`set_terminate_on_drop`]: #method.set_terminate_on_drop
+    #[inline]
+    pub fn terminate_on_drop(&self) -> bool {
+        self.inner.terminate_on_drop.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for DisplayInner {
+    fn drop(&mut self) {
+        let mut displays = DISPLAYS.lock();
+        // if the display was registered again while this was being dropped,
+        // the new registration relies on it staying initialized
+        let key = self.display as usize;
+        if displays.get(&key).is_some_and(|weak| std::ptr::eq(weak.as_ptr(), self)) {
+            displays.remove(&key);
+            if self.terminate_on_drop.load(Ordering::Relaxed) {
+                let egl = EGL.as_ref().unwrap();
+                unsafe { egl.Terminate(self.display) };
+            }
+        }
+    }
+}
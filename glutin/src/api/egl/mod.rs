@@ -12,8 +12,10 @@
 #[cfg(not(target_os = "android"))]
 mod egl {
     use super::ffi;
-    use crate::api::dlloader::{SymTrait, SymWrapper};
+    use crate::api::dlloader::{get_symbol, open_library, SymTrait, SymWrapper};
+    use crate::Api;
     use libloading;
+    use std::ffi::c_void;
     use std::sync::{Arc, Mutex};
 
     #[cfg(unix)]
@@ -88,6 +90,41 @@ mod egl {
             SymWrapper::new(paths).map(|i| Egl(i))
         }
     }
+
+    #[cfg(target_os = "windows")]
+    mod paths {
+        pub const GL: &[&str] = &["opengl32.dll"];
+        pub const GLES1: &[&str] = &["libGLES_CM.dll"];
+        pub const GLES2: &[&str] = &["libGLESv2.dll"];
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    mod paths {
+        pub const GL: &[&str] = &["libGL.so.1", "libOpenGL.so.0", "libGL.so"];
+        pub const GLES1: &[&str] = &["libGLESv1_CM.so.1", "libGLESv1_CM.so"];
+        pub const GLES2: &[&str] = &["libGLESv2.so.2", "libGLESv2.so"];
+    }
+
+    lazy_static! {
+        // Opened on first use by `get_client_symbol`.
+        static ref GL_LIBRARY: Option<libloading::Library> = open_library(paths::GL);
+        static ref GLES1_LIBRARY: Option<libloading::Library> = open_library(paths::GLES1);
+        static ref GLES2_LIBRARY: Option<libloading::Library> = open_library(paths::GLES2);
+    }
+
+    /// Looks a function up in the library of the client API with `dlsym`.
+    ///
+    /// Before EGL 1.5, `eglGetProcAddress` is only required to return
+    /// extension functions, unless `EGL_KHR_get_all_proc_addresses` is
+    /// supported, so the core ones may only be found there.
+    pub fn get_client_symbol(api: Api, major: u8, name: &str) -> *const c_void {
+        let lib = match (api, major) {
+            (Api::OpenGlEs, 1) => &*GLES1_LIBRARY,
+            (Api::OpenGlEs, _) => &*GLES2_LIBRARY,
+            _ => &*GL_LIBRARY,
+        };
+        lib.as_ref().map(|lib| get_symbol(lib, name)).unwrap_or(std::ptr::null())
+    }
 }
 
 #[cfg(target_os = "android")]
@@ -104,19 +141,19 @@ mod egl {
     }
 }
 
+mod display;
 mod make_current_guard;
 
+pub use self::display::EglDisplay;
 pub use self::egl::Egl;
 use self::make_current_guard::MakeCurrentGuard;
 #[cfg(not(target_os = "windows"))]
 use crate::Rect;
 use crate::{
-    Api, ContextError, CreationError, GlAttributes, GlRequest, PixelFormat,
-    PixelFormatRequirements, ReleaseBehavior, Robustness,
+    Api, ContextError, ContextPriority, CreationError, GlAttributes, GlRequest, PixelFormat,
+    PixelFormatRequirements, ReleaseBehavior, RendererInfo, Robustness,
 };
 
-use glutin_egl_sys as ffi;
-use parking_lot::Mutex;
 #[cfg(any(
     target_os = "android",
     target_os = "windows",
@@ -126,7 +163,9 @@ use parking_lot::Mutex;
     target_os = "netbsd",
     target_os = "openbsd",
 ))]
-use winit::dpi;
+use crate::dpi;
+use glutin_egl_sys as ffi;
+use parking_lot::Mutex;
 
 use std::ffi::{CStr, CString};
 use std::ops::{Deref, DerefMut};
@@ -154,8 +193,12 @@ lazy_static! {
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum NativeDisplay {
-    /// `None` means `EGL_DEFAULT_DISPLAY`.
-    X11(Option<ffi::EGLNativeDisplayType>),
+    /// `None` means `EGL_DEFAULT_DISPLAY`. The second field is the screen,
+    /// `None` means the default screen of the display.
+    X11(Option<ffi::EGLNativeDisplayType>, Option<raw::c_int>),
+    /// An `xcb_connection_t`, and the screen like for `X11`. Only supported
+    /// with `EGL_EXT_platform_xcb`.
+    Xcb(ffi::EGLNativeDisplayType, Option<raw::c_int>),
     /// `None` means `EGL_DEFAULT_DISPLAY`.
     Gbm(Option<ffi::EGLNativeDisplayType>),
     /// `None` means `EGL_DEFAULT_DISPLAY`.
@@ -171,55 +214,48 @@ pub enum NativeDisplay {
 
 #[derive(Debug)]
 pub struct Context {
-    display: ffi::egl::types::EGLDisplay,
+    display: EglDisplay,
     context: ffi::egl::types::EGLContext,
     surface: Option<Mutex<ffi::egl::types::EGLSurface>>,
     api: Api,
+    version: (u8, u8),
     pixel_format: PixelFormat,
-    #[cfg(target_os = "android")]
     config_id: ffi::egl::types::EGLConfig,
+    priority: Option<ContextPriority>,
 }
 
 #[cfg(target_os = "android")]
 #[inline]
-fn get_native_display(native_display: &NativeDisplay) -> *const raw::c_void {
+fn get_native_display(_: &NativeDisplay) -> Result<*const raw::c_void, CreationError> {
     let egl = EGL.as_ref().unwrap();
-    unsafe { egl.GetDisplay(ffi::egl::DEFAULT_DISPLAY as *mut _) }
+    Ok(unsafe { egl.GetDisplay(ffi::egl::DEFAULT_DISPLAY as *mut _) })
 }
 
-fn get_egl_version(
-    display: ffi::egl::types::EGLDisplay,
-) -> Result<(ffi::egl::types::EGLint, ffi::egl::types::EGLint), CreationError> {
-    unsafe {
-        let egl = EGL.as_ref().unwrap();
-        let mut major: ffi::egl::types::EGLint = std::mem::zeroed();
-        let mut minor: ffi::egl::types::EGLint = std::mem::zeroed();
-
-        if egl.Initialize(display, &mut major, &mut minor) == 0 {
-            return Err(CreationError::OsError("eglInitialize failed".to_string()));
-        }
-
-        Ok((major, minor))
-    }
-}
+/// An API with the versions of it to try, from the highest to the lowest.
+type ApiVersions = (Api, Vec<(u8, u8)>);
 
+/// Binds the requested API and returns the APIs which can be bound, with their
+/// versions to try. No versions means the driver picks.
+///
+/// Only `GlRequest::Ranges` may return several APIs, the next one being tried
+/// when no version of the previous one can be created.
 unsafe fn bind_and_get_api<'a>(
     opengl: &'a GlAttributes<&'a Context>,
     egl_version: (ffi::egl::types::EGLint, ffi::egl::types::EGLint),
-) -> Result<(Option<(u8, u8)>, Api), CreationError> {
+) -> Result<Vec<ApiVersions>, CreationError> {
     let egl = EGL.as_ref().unwrap();
     match opengl.version {
         GlRequest::Latest => {
             if egl_version >= (1, 4) {
                 if egl.BindAPI(ffi::egl::OPENGL_API) != 0 {
-                    Ok((None, Api::OpenGl))
+                    Ok(vec![(Api::OpenGl, vec![])])
                 } else if egl.BindAPI(ffi::egl::OPENGL_ES_API) != 0 {
-                    Ok((None, Api::OpenGlEs))
+                    Ok(vec![(Api::OpenGlEs, vec![])])
                 } else {
                     Err(CreationError::OpenGlVersionNotSupported)
                 }
             } else {
-                Ok((None, Api::OpenGlEs))
+                Ok(vec![(Api::OpenGlEs, vec![])])
             }
         }
         GlRequest::Specific(Api::OpenGlEs, version) => {
@@ -228,7 +264,7 @@ unsafe fn bind_and_get_api<'a>(
                     return Err(CreationError::OpenGlVersionNotSupported);
                 }
             }
-            Ok((Some(version), Api::OpenGlEs))
+            Ok(vec![(Api::OpenGlEs, vec![version])])
         }
         GlRequest::Specific(Api::OpenGl, version) => {
             if egl_version < (1, 4) {
@@ -237,31 +273,73 @@ unsafe fn bind_and_get_api<'a>(
             if egl.BindAPI(ffi::egl::OPENGL_API) == 0 {
                 return Err(CreationError::OpenGlVersionNotSupported);
             }
-            Ok((Some(version), Api::OpenGl))
+            Ok(vec![(Api::OpenGl, vec![version])])
         }
         GlRequest::Specific(_, _) => Err(CreationError::OpenGlVersionNotSupported),
         GlRequest::GlThenGles { opengles_version, opengl_version } => {
             if egl_version >= (1, 4) {
                 if egl.BindAPI(ffi::egl::OPENGL_API) != 0 {
-                    Ok((Some(opengl_version), Api::OpenGl))
+                    Ok(vec![(Api::OpenGl, vec![opengl_version])])
                 } else if egl.BindAPI(ffi::egl::OPENGL_ES_API) != 0 {
-                    Ok((Some(opengles_version), Api::OpenGlEs))
+                    Ok(vec![(Api::OpenGlEs, vec![opengles_version])])
                 } else {
                     Err(CreationError::OpenGlVersionNotSupported)
                 }
             } else {
-                Ok((Some(opengles_version), Api::OpenGlEs))
+                Ok(vec![(Api::OpenGlEs, vec![opengles_version])])
+            }
+        }
+        GlRequest::Ranges(ref ranges) => {
+            let mut apis = Vec::new();
+            for (api, range) in ranges {
+                let versions = api.versions_in(range);
+                if versions.is_empty() {
+                    continue;
+                }
+                let bound = match api {
+                    Api::OpenGl => egl_version >= (1, 4) && egl.BindAPI(ffi::egl::OPENGL_API) != 0,
+                    Api::OpenGlEs => {
+                        egl_version < (1, 2) || egl.BindAPI(ffi::egl::OPENGL_ES_API) != 0
+                    }
+                    Api::WebGl => false,
+                };
+                if bound {
+                    apis.push((*api, versions));
+                }
+            }
+            // leave the first API bound, as the config is chosen for it
+            match apis.first() {
+                Some(&(api, _)) => {
+                    bind_api(api, egl_version);
+                    Ok(apis)
+                }
+                None => Err(CreationError::OpenGlVersionNotSupported),
             }
         }
     }
 }
 
-#[cfg(not(target_os = "android"))]
-fn get_native_display(native_display: &NativeDisplay) -> *const raw::c_void {
+/// Binds `api`, which `bind_and_get_api` found to be supported.
+unsafe fn bind_api(api: Api, egl_version: (ffi::egl::types::EGLint, ffi::egl::types::EGLint)) {
     let egl = EGL.as_ref().unwrap();
-    // the first step is to query the list of extensions without any display, if
-    // supported
-    let dp_extensions = unsafe {
+    match api {
+        Api::OpenGl => {
+            egl.BindAPI(ffi::egl::OPENGL_API);
+        }
+        Api::OpenGlEs if egl_version >= (1, 2) => {
+            egl.BindAPI(ffi::egl::OPENGL_ES_API);
+        }
+        _ => (),
+    }
+}
+
+/// Queries the list of extensions without any display, if supported.
+pub fn get_client_extensions() -> Vec<String> {
+    let egl = match EGL.as_ref() {
+        Some(egl) => egl,
+        None => return vec![],
+    };
+    unsafe {
         let p = egl.QueryString(ffi::egl::NO_DISPLAY, ffi::egl::EXTENSIONS as i32);
 
         // this possibility is available only with EGL 1.5 or
@@ -274,37 +352,88 @@ fn get_native_display(native_display: &NativeDisplay) -> *const raw::c_void {
             let list = String::from_utf8(p.to_bytes().to_vec()).unwrap_or_else(|_| format!(""));
             list.split(' ').map(|e| e.to_string()).collect::<Vec<_>>()
         }
-    };
+    }
+}
+
+// EGL_EXT_platform_xcb, which the registry of `gl_generator` doesn't know yet
+#[cfg(not(target_os = "android"))]
+const PLATFORM_XCB_EXT: ffi::egl::types::EGLenum = 0x31DC;
+#[cfg(not(target_os = "android"))]
+const PLATFORM_XCB_SCREEN_EXT: ffi::egl::types::EGLenum = 0x31DE;
+
+/// Fails if the display can't be created as asked, e.g. on the given X screen.
+#[cfg(not(target_os = "android"))]
+fn get_native_display(native_display: &NativeDisplay) -> Result<*const raw::c_void, CreationError> {
+    let egl = EGL.as_ref().unwrap();
+    // the first step is to query the list of extensions without any display
+    let dp_extensions = get_client_extensions();
 
     let has_dp_extension = |e: &str| dp_extensions.iter().find(|s| s == &e).is_some();
 
-    match *native_display {
+    Ok(match *native_display {
         // Note: Some EGL implementations are missing the
         // `eglGetPlatformDisplay(EXT)` symbol       despite reporting
         // `EGL_EXT_platform_base`. I'm pretty sure this is a bug.
         //       Therefore we detect whether the symbol is loaded in addition to
         // checking for       extensions.
-        NativeDisplay::X11(display)
+        NativeDisplay::X11(display, screen)
             if has_dp_extension("EGL_KHR_platform_x11") && egl.GetPlatformDisplay.is_loaded() =>
         {
             let d = display.unwrap_or(ffi::egl::DEFAULT_DISPLAY as *const _);
-            // TODO: `PLATFORM_X11_SCREEN_KHR`
+            let mut attribs = Vec::new();
+            if let Some(screen) = screen {
+                attribs.push(ffi::egl::PLATFORM_X11_SCREEN_KHR as ffi::egl::types::EGLAttrib);
+                attribs.push(screen as ffi::egl::types::EGLAttrib);
+            }
+            attribs.push(ffi::egl::NONE as ffi::egl::types::EGLAttrib);
             unsafe {
-                egl.GetPlatformDisplay(ffi::egl::PLATFORM_X11_KHR, d as *mut _, std::ptr::null())
+                egl.GetPlatformDisplay(ffi::egl::PLATFORM_X11_KHR, d as *mut _, attribs.as_ptr())
             }
         }
 
-        NativeDisplay::X11(display)
+        NativeDisplay::X11(display, screen)
             if has_dp_extension("EGL_EXT_platform_x11")
                 && egl.GetPlatformDisplayEXT.is_loaded() =>
         {
             let d = display.unwrap_or(ffi::egl::DEFAULT_DISPLAY as *const _);
-            // TODO: `PLATFORM_X11_SCREEN_EXT`
+            let mut attribs = Vec::new();
+            if let Some(screen) = screen {
+                attribs.push(ffi::egl::PLATFORM_X11_SCREEN_EXT as raw::c_int);
+                attribs.push(screen);
+            }
+            attribs.push(ffi::egl::NONE as raw::c_int);
             unsafe {
-                egl.GetPlatformDisplayEXT(ffi::egl::PLATFORM_X11_EXT, d as *mut _, std::ptr::null())
+                egl.GetPlatformDisplayEXT(ffi::egl::PLATFORM_X11_EXT, d as *mut _, attribs.as_ptr())
             }
         }
 
+        NativeDisplay::Xcb(connection, screen)
+            if has_dp_extension("EGL_EXT_platform_xcb")
+                && egl.GetPlatformDisplayEXT.is_loaded() =>
+        {
+            let mut attribs = Vec::new();
+            if let Some(screen) = screen {
+                attribs.push(PLATFORM_XCB_SCREEN_EXT as raw::c_int);
+                attribs.push(screen);
+            }
+            attribs.push(ffi::egl::NONE as raw::c_int);
+            unsafe {
+                egl.GetPlatformDisplayEXT(PLATFORM_XCB_EXT, connection as *mut _, attribs.as_ptr())
+            }
+        }
+
+        // `eglGetDisplay` takes an Xlib display, not a connection
+        NativeDisplay::Xcb(..) => ffi::egl::NO_DISPLAY,
+
+        // `eglGetDisplay` always uses the default screen
+        NativeDisplay::X11(_, Some(screen)) => {
+            return Err(CreationError::NotSupported(format!(
+                "creating an EGL context on X screen {} requires `EGL_KHR_platform_x11` or \
+                 `EGL_EXT_platform_x11`",
+                screen
+            )))
+        }
+
         NativeDisplay::Gbm(display)
             if has_dp_extension("EGL_KHR_platform_gbm") && egl.GetPlatformDisplay.is_loaded() =>
         {
@@ -377,20 +506,20 @@ fn get_native_display(native_display: &NativeDisplay) -> *const raw::c_void {
             )
         },
 
-        NativeDisplay::X11(Some(display))
+        NativeDisplay::X11(Some(display), _)
         | NativeDisplay::Gbm(Some(display))
         | NativeDisplay::Wayland(Some(display))
         | NativeDisplay::Device(display)
         | NativeDisplay::Other(Some(display)) => unsafe { egl.GetDisplay(display as *mut _) },
 
-        NativeDisplay::X11(None)
+        NativeDisplay::X11(None, _)
         | NativeDisplay::Gbm(None)
         | NativeDisplay::Wayland(None)
         | NativeDisplay::Android
         | NativeDisplay::Other(None) => unsafe {
             egl.GetDisplay(ffi::egl::DEFAULT_DISPLAY as *mut _)
         },
-    }
+    })
 }
 
 #[allow(dead_code)] // Not all platforms use all
@@ -419,38 +548,26 @@ impl Context {
         F: FnMut(
             Vec<ffi::egl::types::EGLConfig>,
             ffi::egl::types::EGLDisplay,
-        ) -> Result<ffi::egl::types::EGLConfig, ()>,
+        ) -> Result<ffi::egl::types::EGLConfig, CreationError>,
     {
-        let egl = EGL.as_ref().unwrap();
-        // calling `eglGetDisplay` or equivalent
-        let display = get_native_display(&native_display);
-
-        if display.is_null() {
-            return Err(CreationError::OsError("Could not create EGL display object".to_string()));
-        }
-
-        let egl_version = get_egl_version(display)?;
-
-        // the list of extensions supported by the client once initialized is
-        // different from the list of extensions obtained earlier
-        let extensions = if egl_version >= (1, 2) {
-            let p =
-                unsafe { CStr::from_ptr(egl.QueryString(display, ffi::egl::EXTENSIONS as i32)) };
-            let list = String::from_utf8(p.to_bytes().to_vec()).unwrap_or_else(|_| format!(""));
-            list.split(' ').map(|e| e.to_string()).collect::<Vec<_>>()
-        } else {
-            vec![]
+        let display = match pf_reqs.egl_display {
+            Some(ref display) => display.clone(),
+            None => EglDisplay::get(&native_display)?,
         };
+        let egl_version = display.version();
 
         // binding the right API and choosing the version
-        let (version, api) = unsafe { bind_and_get_api(&opengl, egl_version)? };
+        let apis = unsafe { bind_and_get_api(&opengl, egl_version)? };
 
-        let (config_id, pixel_format) = unsafe {
+        // the config has to suit the lowest version of the first API we may
+        // end up creating, the other ones are only tried if it suits them too
+        let (api, ref versions) = apis[0];
+        let (config_id, mut pixel_format) = unsafe {
             choose_fbconfig(
-                display,
+                display.raw_handle(),
                 &egl_version,
                 api,
-                version,
+                versions.last().copied(),
                 pf_reqs,
                 surface_type,
                 opengl,
@@ -458,13 +575,18 @@ impl Context {
             )?
         };
 
+        // Wayland composites every surface with an alpha channel, on X11 it
+        // depends on the visual, see `ContextPrototype::set_transparency`
+        if let NativeDisplay::Wayland(_) = native_display {
+            pixel_format.transparency =
+                surface_type == SurfaceType::Window && pixel_format.alpha_bits > 0;
+        }
+
         Ok(ContextPrototype {
             opengl,
             display,
-            egl_version,
-            extensions,
-            api,
-            version,
+            release_behavior: pf_reqs.release_behavior,
+            apis,
             config_id,
             pixel_format,
         })
@@ -487,7 +609,7 @@ impl Context {
     pub unsafe fn make_current(&self) -> Result<(), ContextError> {
         let egl = EGL.as_ref().unwrap();
         let surface = self.surface.as_ref().map(|s| *s.lock()).unwrap_or(ffi::egl::NO_SURFACE);
-        let ret = egl.MakeCurrent(self.display, surface, surface, self.context);
+        let ret = egl.MakeCurrent(self.display.raw_handle(), surface, surface, self.context);
 
         self.check_make_current(Some(ret))
     }
@@ -504,7 +626,7 @@ impl Context {
 
         if surface_eq || egl.GetCurrentContext() == self.context {
             let ret = egl.MakeCurrent(
-                self.display,
+                self.display.raw_handle(),
                 ffi::egl::NO_SURFACE,
                 ffi::egl::NO_SURFACE,
                 ffi::egl::NO_CONTEXT,
@@ -527,14 +649,78 @@ impl Context {
         self.api
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        Some(self.version)
+    }
+
     #[inline]
     pub unsafe fn raw_handle(&self) -> ffi::egl::types::EGLContext {
         self.context
     }
 
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        self.priority
+    }
+
     #[inline]
     pub unsafe fn get_egl_display(&self) -> ffi::egl::types::EGLDisplay {
-        self.display
+        self.display.raw_handle()
+    }
+
+    #[inline]
+    pub fn egl_display(&self) -> &EglDisplay {
+        &self.display
+    }
+
+    /// Replaces the pbuffer surface with one of the given size. The context,
+    /// and so every object created with it, is kept.
+    ///
+    /// If the context is current on this thread, the new surface is made
+    /// current in place of the old one. On failure, the old surface is kept.
+    pub fn resize_pbuffer(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        let egl = EGL.as_ref().unwrap();
+        let mut surface = self.surface.as_ref().unwrap().lock();
+        let attrs = &[
+            ffi::egl::WIDTH as raw::c_int,
+            width as raw::c_int,
+            ffi::egl::HEIGHT as raw::c_int,
+            height as raw::c_int,
+            ffi::egl::NONE as raw::c_int,
+        ];
+
+        unsafe {
+            let new_surface =
+                egl.CreatePbufferSurface(self.display.raw_handle(), self.config_id, attrs.as_ptr());
+            if new_surface.is_null() || new_surface == ffi::egl::NO_SURFACE {
+                return Err(ContextError::OsError(format!(
+                    "eglCreatePbufferSurface failed with 0x{:x}",
+                    egl.GetError()
+                )));
+            }
+            if self.is_current() {
+                let ret = egl.MakeCurrent(
+                    self.display.raw_handle(),
+                    new_surface,
+                    new_surface,
+                    self.context,
+                );
+                if ret == 0 {
+                    let err = egl.GetError();
+                    egl.DestroySurface(self.display.raw_handle(), new_surface);
+                    return Err(match err as u32 {
+                        ffi::egl::CONTEXT_LOST => ContextError::ContextLost,
+                        err => {
+                            ContextError::OsError(format!("eglMakeCurrent failed with 0x{:x}", err))
+                        }
+                    });
+                }
+            }
+            egl.DestroySurface(self.display.raw_handle(), *surface);
+            *surface = new_surface;
+        }
+        Ok(())
     }
 
     // Handle Android Life Cycle.
@@ -548,11 +734,16 @@ impl Context {
         if *surface != ffi::egl::NO_SURFACE {
             return;
         }
-        *surface = egl.CreateWindowSurface(self.display, self.config_id, nwin, std::ptr::null());
+        *surface = egl.CreateWindowSurface(
+            self.display.raw_handle(),
+            self.config_id,
+            nwin,
+            std::ptr::null(),
+        );
         if surface.is_null() {
             panic!("on_surface_created: eglCreateWindowSurface failed with 0x{:x}", egl.GetError())
         }
-        let ret = egl.MakeCurrent(self.display, *surface, *surface, self.context);
+        let ret = egl.MakeCurrent(self.display.raw_handle(), *surface, *surface, self.context);
         if ret == 0 {
             panic!("on_surface_created: eglMakeCurrent failed with 0x{:x}", egl.GetError())
         }
@@ -570,7 +761,7 @@ impl Context {
             return;
         }
         let ret = egl.MakeCurrent(
-            self.display,
+            self.display.raw_handle(),
             ffi::egl::NO_SURFACE,
             ffi::egl::NO_SURFACE,
             ffi::egl::NO_CONTEXT,
@@ -579,16 +770,21 @@ impl Context {
             panic!("on_surface_destroyed: eglMakeCurrent failed with 0x{:x}", egl.GetError())
         }
 
-        egl.DestroySurface(self.display, *surface);
+        egl.DestroySurface(self.display.raw_handle(), *surface);
         *surface = ffi::egl::NO_SURFACE;
     }
 
     #[inline]
     pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
         let egl = EGL.as_ref().unwrap();
-        let addr = CString::new(addr.as_bytes()).unwrap();
-        let addr = addr.as_ptr();
-        unsafe { egl.GetProcAddress(addr) as *const _ }
+        let c_addr = CString::new(addr.as_bytes()).unwrap();
+        let ptr = unsafe { egl.GetProcAddress(c_addr.as_ptr()) as *const core::ffi::c_void };
+
+        #[cfg(not(target_os = "android"))]
+        if ptr.is_null() {
+            return egl::get_client_symbol(self.api, self.version.0, addr);
+        }
+        ptr
     }
 
     #[inline]
@@ -599,7 +795,7 @@ impl Context {
             return Err(ContextError::ContextLost);
         }
 
-        let ret = unsafe { egl.SwapBuffers(self.display, *surface) };
+        let ret = unsafe { egl.SwapBuffers(self.display.raw_handle(), *surface) };
 
         if ret == 0 {
             match unsafe { egl.GetError() } as u32 {
@@ -638,7 +834,7 @@ impl Context {
 
         let ret = unsafe {
             egl.SwapBuffersWithDamageKHR(
-                self.display,
+                self.display.raw_handle(),
                 *surface,
                 ffirects.as_mut_ptr(),
                 rects.len() as ffi::egl::types::EGLint,
@@ -664,10 +860,183 @@ impl Context {
         egl.SwapBuffersWithDamageKHR.is_loaded()
     }
 
+    #[inline]
+    #[cfg(not(target_os = "windows"))]
+    pub fn set_damage_region(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        if !self.set_damage_region_supported() {
+            return Err(ContextError::FunctionUnavailable);
+        }
+
+        let egl = EGL.as_ref().unwrap();
+
+        let surface = self.surface.as_ref().unwrap().lock();
+        if *surface == ffi::egl::NO_SURFACE {
+            return Err(ContextError::ContextLost);
+        }
+
+        let mut ffirects: Vec<ffi::egl::types::EGLint> = Vec::with_capacity(rects.len() * 4);
+
+        for rect in rects {
+            ffirects.push(rect.x as ffi::egl::types::EGLint);
+            ffirects.push(rect.y as ffi::egl::types::EGLint);
+            ffirects.push(rect.width as ffi::egl::types::EGLint);
+            ffirects.push(rect.height as ffi::egl::types::EGLint);
+        }
+
+        let ret = unsafe {
+            egl.SetDamageRegionKHR(
+                self.display.raw_handle(),
+                *surface,
+                ffirects.as_mut_ptr(),
+                rects.len() as ffi::egl::types::EGLint,
+            )
+        };
+
+        if ret == ffi::egl::FALSE {
+            // `EGL_BAD_ACCESS` is returned when the region was already set
+            // for this frame or when the back buffer was already drawn to
+            match unsafe { egl.GetError() } as u32 {
+                ffi::egl::CONTEXT_LOST => Err(ContextError::ContextLost),
+                err => Err(ContextError::OsError(format!(
+                    "eglSetDamageRegionKHR failed (eglGetError returned 0x{:x})",
+                    err
+                ))),
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    #[cfg(not(target_os = "windows"))]
+    pub fn set_damage_region_supported(&self) -> bool {
+        let egl = EGL.as_ref().unwrap();
+        // the function may be loaded from a library supporting the extension
+        // on other displays
+        self.display.has_extension("EGL_KHR_partial_update") && egl.SetDamageRegionKHR.is_loaded()
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        if !self.display.has_extension("EGL_EXT_buffer_age") {
+            return None;
+        }
+
+        let egl = EGL.as_ref().unwrap();
+        let surface = self.surface.as_ref()?.lock();
+        if *surface == ffi::egl::NO_SURFACE {
+            return None;
+        }
+
+        let mut age = 0;
+        let ret = unsafe {
+            egl.QuerySurface(
+                self.display.raw_handle(),
+                *surface,
+                ffi::egl::BUFFER_AGE_EXT as i32,
+                &mut age,
+            )
+        };
+        if ret == ffi::egl::FALSE {
+            None
+        } else {
+            Some(age as u32)
+        }
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         self.pixel_format.clone()
     }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        self.display.extensions()
+    }
+
+    /// Must be called with the context current.
+    pub fn renderer_info(&self) -> RendererInfo {
+        let mut info = RendererInfo::from_gl(|addr| self.get_proc_address(addr));
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+        ))]
+        self.query_device(&mut info);
+        info
+    }
+
+    /// With `EGL_EXT_device_query`, the device of the display tells whether
+    /// it is a software one and, with `EGL_EXT_device_drm`, its DRM node
+    /// leads to its PCI ids.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    fn query_device(&self, info: &mut RendererInfo) {
+        let egl = EGL.as_ref().unwrap();
+        if !self
+            .display
+            .client_extensions()
+            .iter()
+            .any(|e| e == "EGL_EXT_device_query" || e == "EGL_EXT_device_base")
+            || !egl.QueryDisplayAttribEXT.is_loaded()
+            || !egl.QueryDeviceStringEXT.is_loaded()
+        {
+            return;
+        }
+
+        let mut device = 0;
+        let ret = unsafe {
+            egl.QueryDisplayAttribEXT(
+                self.display.raw_handle(),
+                ffi::egl::DEVICE_EXT as _,
+                &mut device,
+            )
+        };
+        if ret == 0 {
+            return;
+        }
+        let query_string = |name| unsafe {
+            let p = egl.QueryDeviceStringEXT(device as *mut _, name as _);
+            if p.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(p).to_string_lossy().into_owned())
+            }
+        };
+
+        let extensions = query_string(ffi::egl::EXTENSIONS).unwrap_or_default();
+        let has_extension = |ext| extensions.split(' ').any(|e| e == ext);
+        if has_extension("EGL_MESA_device_software") {
+            info.software = true;
+        }
+        if has_extension("EGL_EXT_device_drm") {
+            if let Some((vendor_id, device_id)) =
+                query_string(ffi::egl::DRM_DEVICE_FILE_EXT).and_then(|node| drm_pci_ids(&node))
+            {
+                info.vendor_id = Some(vendor_id);
+                info.device_id = Some(device_id);
+            }
+        }
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    #[cfg(feature = "x11")]
+    pub fn get_native_visual_id(&self) -> ffi::egl::types::EGLint {
+        get_native_visual_id(self.display.raw_handle(), self.config_id)
+    }
 }
 
 unsafe impl Send for Context {}
@@ -682,78 +1051,29 @@ impl Drop for Context {
             // Ok, so we got to call `glFinish` before destroying the context
             // to ensure it actually gets destroyed. This requires making the
             // this context current.
-            let mut guard = MakeCurrentGuard::new(self.display, surface, surface, self.context)
-                .map_err(|err| ContextError::OsError(err))
-                .unwrap();
+            let mut guard =
+                MakeCurrentGuard::new(self.display.raw_handle(), surface, surface, self.context)
+                    .map_err(|err| ContextError::OsError(err))
+                    .unwrap();
 
             guard.if_any_same_then_invalidate(surface, surface, self.context);
 
             let gl_finish_fn = self.get_proc_address("glFinish");
-            assert!(gl_finish_fn != std::ptr::null());
-            let gl_finish_fn = std::mem::transmute::<_, extern "system" fn()>(gl_finish_fn);
-            gl_finish_fn();
+            if !gl_finish_fn.is_null() {
+                let gl_finish_fn = std::mem::transmute::<_, extern "system" fn()>(gl_finish_fn);
+                gl_finish_fn();
+            }
 
-            egl.DestroyContext(self.display, self.context);
+            egl.DestroyContext(self.display.raw_handle(), self.context);
             self.context = ffi::egl::NO_CONTEXT;
-            egl.DestroySurface(self.display, surface);
+            egl.DestroySurface(self.display.raw_handle(), surface);
             if let Some(ref surface) = self.surface {
                 let mut surface = surface.lock();
                 *surface = ffi::egl::NO_SURFACE;
             }
 
-            // In a reasonable world, we could uncomment the line bellow.
-            //
-            // This is no such world. Lets talk about something.
-            //
-            // You see, every call to `get_native_display` returns the exact
-            // same display, just look at the docs:
-            //
-            // "Multiple calls made to eglGetDisplay with the same display_id
-            // will return the same EGLDisplay handle."
-            //
-            // My EGL implementation does not do any ref counting, nor do the
-            // EGL docs mention ref counting anywhere. In fact, the docs state
-            // that there will be *no effect*, which, in a way, implies no ref
-            // counting:
-            //
-            // "Initializing an already initialized EGL display connection has
-            // no effect besides returning the version numbers."
-            //
-            // So, if we terminate the display, other people who are using it
-            // won't be so happy.
-            //
-            // Well, how did I stumble on this issue you might ask...
-            //
-            // In this case, the "other people" was us, for it appears my EGL
-            // implementation does not follow the docs,  or maybe I'm misreading
-            // them. You see, according to the egl docs:
-            //
-            // "To release the current context without assigning a new one, set
-            // context to EGL_NO_CONTEXT and set draw and read to
-            // EGL_NO_SURFACE.  [...] ******This is the only case where an
-            // uninitialized display may be passed to eglMakeCurrent.******"
-            // (Emphasis mine).
-            //
-            // Well, my computer returns EGL_BAD_DISPLAY if the display passed
-            // to eglMakeCurrent is uninitialized, which allowed to me to spot
-            // this issue.
-            //
-            // I would have assumed that if EGL was going to provide us with
-            // the same EGLDisplay that they'd at least do
-            // some ref counting, but they don't.
-            //
-            // FIXME: Technically we are leaking resources, not much we can do.
-            // Yeah, we could have a global static that does ref counting
-            // ourselves, but what if some other library is using the display.
-            //
-            // On unix operating systems, we could preload a little lib that
-            // does ref counting on that level, but:
-            //      A) What about other platforms?
-            //      B) Do you *really* want all glutin programs to preload a
-            //      library?
-            //      C) Who the hell is going to maintain that?
-            //
-            // egl.Terminate(self.display);
+            // the display gets terminated when `self.display` is dropped, if
+            // this was its last context
         }
     }
 }
@@ -761,15 +1081,72 @@ impl Drop for Context {
 #[derive(Debug)]
 pub struct ContextPrototype<'a> {
     opengl: &'a GlAttributes<&'a Context>,
-    display: ffi::egl::types::EGLDisplay,
-    egl_version: (ffi::egl::types::EGLint, ffi::egl::types::EGLint),
-    extensions: Vec<String>,
-    api: Api,
-    version: Option<(u8, u8)>,
+    display: EglDisplay,
+    release_behavior: ReleaseBehavior,
+    apis: Vec<ApiVersions>,
     config_id: ffi::egl::types::EGLConfig,
     pixel_format: PixelFormat,
 }
 
+/// Reads the PCI ids of a DRM node, e.g. `/dev/dri/card0`, from sysfs.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn drm_pci_ids(node: &str) -> Option<(u32, u32)> {
+    let name = std::path::Path::new(node).file_name()?;
+    let device = std::path::Path::new("/sys/class/drm").join(name).join("device");
+    let read_id = |file| parse_pci_id(&std::fs::read_to_string(device.join(file)).ok()?);
+    Some((read_id("vendor")?, read_id("device")?))
+}
+
+/// Parses a PCI id as written in sysfs, e.g. `"0x8086\n"`.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn parse_pci_id(id: &str) -> Option<u32> {
+    let id = id.trim();
+    u32::from_str_radix(id.strip_prefix("0x").unwrap_or(id), 16).ok()
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+#[cfg(feature = "wayland")]
+pub fn get_alpha_size(
+    display: ffi::egl::types::EGLDisplay,
+    config_id: ffi::egl::types::EGLConfig,
+) -> Result<ffi::egl::types::EGLint, CreationError> {
+    let egl = EGL.as_ref().unwrap();
+    let mut value = unsafe { std::mem::zeroed() };
+    let ret = unsafe {
+        egl.GetConfigAttrib(
+            display,
+            config_id,
+            ffi::egl::ALPHA_SIZE as ffi::egl::types::EGLint,
+            &mut value,
+        )
+    };
+    if ret == 0 {
+        return Err(CreationError::OsError(format!(
+            "eglGetConfigAttrib failed with 0x{:x}",
+            unsafe { egl.GetError() }
+        )));
+    };
+    Ok(value)
+}
+
 #[cfg(any(
     target_os = "linux",
     target_os = "dragonfly",
@@ -810,14 +1187,32 @@ impl<'a> ContextPrototype<'a> {
     ))]
     #[cfg(feature = "x11")]
     pub fn get_native_visual_id(&self) -> ffi::egl::types::EGLint {
-        get_native_visual_id(self.display, self.config_id)
+        get_native_visual_id(self.display.raw_handle(), self.config_id)
+    }
+
+    /// Records whether the visual of the config supports transparency, which
+    /// EGL itself can't tell.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    #[cfg(feature = "x11")]
+    pub fn set_transparency(&mut self, transparency: bool) {
+        self.pixel_format.transparency = transparency;
     }
 
     pub fn finish(self, nwin: ffi::EGLNativeWindowType) -> Result<Context, CreationError> {
         let egl = EGL.as_ref().unwrap();
         let surface = unsafe {
-            let surface =
-                egl.CreateWindowSurface(self.display, self.config_id, nwin, std::ptr::null());
+            let surface = egl.CreateWindowSurface(
+                self.display.raw_handle(),
+                self.config_id,
+                nwin,
+                std::ptr::null(),
+            );
             if surface.is_null() {
                 return Err(CreationError::OsError("eglCreateWindowSurface failed".to_string()));
             }
@@ -837,7 +1232,7 @@ impl<'a> ContextPrototype<'a> {
     pub fn finish_surfaceless(self) -> Result<Context, CreationError> {
         // FIXME: Also check for the GL_OES_surfaceless_context *CONTEXT*
         // extension
-        if self.extensions.iter().find(|s| s == &"EGL_KHR_surfaceless_context").is_none() {
+        if !self.display.has_extension("EGL_KHR_surfaceless_context") {
             Err(CreationError::NotSupported("EGL surfaceless not supported".to_string()))
         } else {
             self.finish_impl(None)
@@ -871,7 +1266,8 @@ impl<'a> ContextPrototype<'a> {
         ];
 
         let surface = unsafe {
-            let surface = egl.CreatePbufferSurface(self.display, self.config_id, attrs.as_ptr());
+            let surface =
+                egl.CreatePbufferSurface(self.display.raw_handle(), self.config_id, attrs.as_ptr());
             if surface.is_null() || surface == ffi::egl::NO_SURFACE {
                 return Err(CreationError::OsError("eglCreatePbufferSurface failed".to_string()));
             }
@@ -890,99 +1286,90 @@ impl<'a> ContextPrototype<'a> {
             None => std::ptr::null(),
         };
 
-        let context = unsafe {
-            if let Some(version) = self.version {
-                create_context(
-                    self.display,
-                    &self.egl_version,
-                    &self.extensions,
-                    self.api,
-                    version,
-                    self.config_id,
-                    self.opengl.debug,
-                    self.opengl.robustness,
-                    share,
-                )?
-            } else if self.api == Api::OpenGlEs {
-                if let Ok(ctx) = create_context(
-                    self.display,
-                    &self.egl_version,
-                    &self.extensions,
-                    self.api,
-                    (2, 0),
-                    self.config_id,
-                    self.opengl.debug,
-                    self.opengl.robustness,
-                    share,
-                ) {
-                    ctx
-                } else if let Ok(ctx) = create_context(
-                    self.display,
-                    &self.egl_version,
-                    &self.extensions,
-                    self.api,
-                    (1, 0),
-                    self.config_id,
-                    self.opengl.debug,
-                    self.opengl.robustness,
-                    share,
-                ) {
-                    ctx
-                } else {
-                    return Err(CreationError::OpenGlVersionNotSupported);
+        let mut result = Err(CreationError::OpenGlVersionNotSupported);
+        for (i, (api, versions)) in self.apis.iter().enumerate() {
+            let api = *api;
+            if i > 0 {
+                // the config was chosen for the first API
+                let renderable =
+                    unsafe { config_renders(self.display.raw_handle(), self.config_id, api) };
+                if !renderable {
+                    continue;
                 }
+                unsafe { bind_api(api, self.display.version()) };
+            }
+
+            // without a requested version, walk down from the latest one that
+            // doesn't need a profile
+            let versions = if !versions.is_empty() {
+                versions.clone()
+            } else if api == Api::OpenGlEs {
+                Api::OpenGlEs.versions_in(&((1, 0)..=(2, 0)))
             } else {
-                if let Ok(ctx) = create_context(
-                    self.display,
-                    &self.egl_version,
-                    &self.extensions,
-                    self.api,
-                    (3, 2),
-                    self.config_id,
-                    self.opengl.debug,
-                    self.opengl.robustness,
-                    share,
-                ) {
-                    ctx
-                } else if let Ok(ctx) = create_context(
-                    self.display,
-                    &self.egl_version,
-                    &self.extensions,
-                    self.api,
-                    (3, 1),
-                    self.config_id,
-                    self.opengl.debug,
-                    self.opengl.robustness,
-                    share,
-                ) {
-                    ctx
-                } else if let Ok(ctx) = create_context(
-                    self.display,
-                    &self.egl_version,
-                    &self.extensions,
-                    self.api,
-                    (1, 0),
-                    self.config_id,
-                    self.opengl.debug,
-                    self.opengl.robustness,
-                    share,
-                ) {
-                    ctx
-                } else {
-                    return Err(CreationError::OpenGlVersionNotSupported);
+                Api::OpenGl.versions_in(&((1, 0)..=(3, 2)))
+            };
+
+            for &version in &versions {
+                result = unsafe {
+                    create_context(
+                        self.display.raw_handle(),
+                        &self.display.version(),
+                        self.display.extensions(),
+                        api,
+                        version,
+                        self.config_id,
+                        self.opengl.debug,
+                        self.opengl.robustness,
+                        self.opengl.priority,
+                        self.release_behavior,
+                        share,
+                    )
                 }
+                .map(|context| (context, api, version));
+                if result.is_ok() {
+                    break;
+                }
+            }
+            match result {
+                Err(CreationError::OpenGlVersionNotSupported) => (),
+                _ => break,
+            }
+        }
+        let (context, api, version) = result?;
+
+        // the driver may not grant the priority asked for
+        let priority = if self.display.has_extension("EGL_IMG_context_priority") {
+            let egl = EGL.as_ref().unwrap();
+            let mut value = 0;
+            let ok = unsafe {
+                egl.QueryContext(
+                    self.display.raw_handle(),
+                    context,
+                    ffi::egl::CONTEXT_PRIORITY_LEVEL_IMG as raw::c_int,
+                    &mut value,
+                )
+            };
+            match value as u32 {
+                _ if ok == ffi::egl::FALSE => None,
+                ffi::egl::CONTEXT_PRIORITY_HIGH_IMG => Some(ContextPriority::High),
+                ffi::egl::CONTEXT_PRIORITY_MEDIUM_IMG => Some(ContextPriority::Medium),
+                ffi::egl::CONTEXT_PRIORITY_LOW_IMG => Some(ContextPriority::Low),
+                _ => None,
             }
+        } else {
+            None
         };
 
         if let Some(surface) = surface {
             // VSync defaults to enabled; disable it if it was not requested.
             if !self.opengl.vsync {
-                let _guard = MakeCurrentGuard::new(self.display, surface, surface, context)
-                    .map_err(|err| CreationError::OsError(err))?;
+                let _guard =
+                    MakeCurrentGuard::new(self.display.raw_handle(), surface, surface, context)
+                        .map_err(|err| CreationError::OsError(err))?;
 
                 let egl = EGL.as_ref().unwrap();
                 unsafe {
-                    if egl.SwapInterval(self.display, 0) == ffi::egl::FALSE {
+                    if egl.SwapInterval(self.display.raw_handle(), 0) == ffi::egl::FALSE {
                         panic!("finish_impl: eglSwapInterval failed: 0x{:x}", egl.GetError());
                     }
                 }
@@ -993,14 +1380,39 @@ impl<'a> ContextPrototype<'a> {
             display: self.display,
             context,
             surface: surface.map(|s| Mutex::new(s)),
-            api: self.api,
+            api,
+            version,
             pixel_format: self.pixel_format,
-            #[cfg(target_os = "android")]
             config_id: self.config_id,
+            priority,
         })
     }
 }
 
+/// Returns whether contexts of any version of `api` can render to `config`.
+unsafe fn config_renders(
+    display: ffi::egl::types::EGLDisplay,
+    config: ffi::egl::types::EGLConfig,
+    api: Api,
+) -> bool {
+    let egl = EGL.as_ref().unwrap();
+    let mut value = 0;
+    let ok = egl.GetConfigAttrib(
+        display,
+        config,
+        ffi::egl::RENDERABLE_TYPE as ffi::egl::types::EGLint,
+        &mut value,
+    );
+    let bits = match api {
+        Api::OpenGl => ffi::egl::OPENGL_BIT,
+        Api::OpenGlEs => {
+            ffi::egl::OPENGL_ES_BIT | ffi::egl::OPENGL_ES2_BIT | ffi::egl::OPENGL_ES3_BIT
+        }
+        Api::WebGl => 0,
+    };
+    ok != ffi::egl::FALSE && value as u32 & bits != 0
+}
+
 unsafe fn choose_fbconfig<F>(
     display: ffi::egl::types::EGLDisplay,
     egl_version: &(ffi::egl::types::EGLint, ffi::egl::types::EGLint),
@@ -1015,7 +1427,7 @@ where
     F: FnMut(
         Vec<ffi::egl::types::EGLConfig>,
         ffi::egl::types::EGLDisplay,
-    ) -> Result<ffi::egl::types::EGLConfig, ()>,
+    ) -> Result<ffi::egl::types::EGLConfig, CreationError>,
 {
     let egl = EGL.as_ref().unwrap();
 
@@ -1121,20 +1533,22 @@ where
             return Err(CreationError::NoAvailablePixelFormat);
         }
 
-        if let Some(xid) = pf_reqs.x11_visual_xid {
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+        ))]
+        if let Some(xid) = pf_reqs.x11.visual_xid {
             out.push(ffi::egl::NATIVE_VISUAL_ID as raw::c_int);
             out.push(xid as raw::c_int);
         }
 
         // FIXME: srgb is not taken into account
 
-        match pf_reqs.release_behavior {
-            ReleaseBehavior::Flush => (),
-            ReleaseBehavior::None => {
-                // TODO: with EGL you need to manually set the behavior
-                unimplemented!()
-            }
-        }
+        // the release behavior is an attribute of the context, see
+        // `create_context`
 
         out.push(ffi::egl::NONE as raw::c_int);
         out
@@ -1203,8 +1617,7 @@ where
         return Err(CreationError::NoAvailablePixelFormat);
     }
 
-    let config_id =
-        config_selector(config_ids, display).map_err(|_| CreationError::NoAvailablePixelFormat)?;
+    let config_id = config_selector(config_ids, display)?;
 
     // analyzing each config
     macro_rules! attrib {
@@ -1239,6 +1652,7 @@ where
             a => Some(a as u16),
         },
         srgb: false, // TODO: use EGL_KHR_gl_colorspace to know that
+        transparency: false,
     };
 
     Ok((config_id, desc))
@@ -1253,6 +1667,8 @@ unsafe fn create_context(
     config_id: ffi::egl::types::EGLConfig,
     gl_debug: bool,
     gl_robustness: Robustness,
+    gl_priority: Option<ContextPriority>,
+    release_behavior: ReleaseBehavior,
     share: ffi::EGLContext,
 ) -> Result<ffi::egl::types::EGLContext, CreationError> {
     let egl = EGL.as_ref().unwrap();
@@ -1358,6 +1774,32 @@ unsafe fn create_context(
         context_attributes.push(version.0 as i32);
     }
 
+    // the priority is only a hint, so it's left out if unsupported
+    if let Some(priority) = gl_priority {
+        if extensions.iter().any(|s| s == "EGL_IMG_context_priority") {
+            context_attributes.push(ffi::egl::CONTEXT_PRIORITY_LEVEL_IMG as i32);
+            context_attributes.push(match priority {
+                ContextPriority::High => ffi::egl::CONTEXT_PRIORITY_HIGH_IMG,
+                ContextPriority::Medium => ffi::egl::CONTEXT_PRIORITY_MEDIUM_IMG,
+                ContextPriority::Low => ffi::egl::CONTEXT_PRIORITY_LOW_IMG,
+            } as i32);
+        }
+    }
+
+    match release_behavior {
+        ReleaseBehavior::Flush => (),
+        ReleaseBehavior::None => {
+            if extensions.iter().any(|s| s == "EGL_KHR_context_flush_control") {
+                context_attributes.push(ffi::egl::CONTEXT_RELEASE_BEHAVIOR_KHR as i32);
+                context_attributes.push(ffi::egl::CONTEXT_RELEASE_BEHAVIOR_NONE_KHR as i32);
+            } else {
+                return Err(CreationError::NotSupported(
+                    "`ReleaseBehavior::None` requires `EGL_KHR_context_flush_control`".to_string(),
+                ));
+            }
+        }
+    }
+
     context_attributes.push(ffi::egl::NONE as i32);
 
     let context = egl.CreateContext(display, config_id, share, context_attributes.as_ptr());
@@ -1373,3 +1815,25 @@ unsafe fn create_context(
 
     Ok(context)
 }
+
+#[cfg(test)]
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pci_ids_from_sysfs() {
+        assert_eq!(parse_pci_id("0x8086\n"), Some(0x8086));
+        assert_eq!(parse_pci_id("0x1002"), Some(0x1002));
+        assert_eq!(parse_pci_id("10de"), Some(0x10de));
+        assert_eq!(parse_pci_id(""), None);
+        assert_eq!(parse_pci_id("0xzz"), None);
+        assert_eq!(parse_pci_id("0x0x10de"), None);
+    }
+}
@@ -35,6 +35,12 @@ mod glx {
 
             SymWrapper::new(paths).map(|i| Glx(i))
         }
+
+        /// Looks a function up in libGL with `dlsym`, for the ones
+        /// `glXGetProcAddress` doesn't return.
+        pub fn get_symbol(&self, name: &str) -> *const std::ffi::c_void {
+            self.0.get_symbol(name)
+        }
     }
 
     impl Deref for Glx {
@@ -56,13 +62,14 @@ pub use self::glx::Glx;
 use self::make_current_guard::MakeCurrentGuard;
 use crate::{
     Api, ContextError, CreationError, GlAttributes, GlProfile, GlRequest, PixelFormat,
-    PixelFormatRequirements, ReleaseBehavior, Robustness,
+    PixelFormatRequirements, Rect, ReleaseBehavior, RendererInfo, Robustness,
 };
 
+use crate::dpi;
 use crate::platform::unix::x11::XConnection;
 use crate::platform_impl::x11_utils::SurfaceType;
 use glutin_glx_sys as ffi;
-use winit::dpi;
+use parking_lot::Mutex;
 
 use std::ffi::{CStr, CString};
 use std::os::raw;
@@ -75,9 +82,15 @@ lazy_static! {
 #[derive(Debug)]
 pub struct Context {
     xconn: Arc<XConnection>,
-    drawable: ffi::Window,
+    drawable: Mutex<ffi::Window>,
+    // only set for pbuffer contexts, to recreate the pbuffer when resized
+    pbuffer_config: Option<ffi::glx::types::GLXFBConfig>,
     context: ffi::GLXContext,
+    extensions: Vec<String>,
+    extra_functions: ExtraFunctions,
+    version: Option<(u8, u8)>,
     pixel_format: PixelFormat,
+    visual_infos: ffi::XVisualInfo,
 }
 
 impl Context {
@@ -134,14 +147,17 @@ impl Context {
     #[inline]
     pub unsafe fn make_current(&self) -> Result<(), ContextError> {
         let glx = GLX.as_ref().unwrap();
-        let res = glx.MakeCurrent(self.xconn.display as *mut _, self.drawable, self.context);
+        let drawable = self.drawable.lock();
+        let res = glx.MakeCurrent(self.xconn.display as *mut _, *drawable, self.context);
         self.check_make_current(Some(res))
     }
 
     #[inline]
     pub unsafe fn make_not_current(&self) -> Result<(), ContextError> {
         let glx = GLX.as_ref().unwrap();
-        if self.drawable == glx.GetCurrentDrawable() || self.context == glx.GetCurrentContext() {
+        if *self.drawable.lock() == glx.GetCurrentDrawable()
+            || self.context == glx.GetCurrentContext()
+        {
             let res = glx.MakeCurrent(self.xconn.display as *mut _, 0, std::ptr::null());
             self.check_make_current(Some(res))
         } else {
@@ -160,6 +176,11 @@ impl Context {
         crate::Api::OpenGl
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        self.version
+    }
+
     #[inline]
     pub unsafe fn raw_handle(&self) -> ffi::GLXContext {
         self.context
@@ -168,16 +189,21 @@ impl Context {
     #[inline]
     pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
         let glx = GLX.as_ref().unwrap();
-        let addr = CString::new(addr.as_bytes()).unwrap();
-        let addr = addr.as_ptr();
-        unsafe { glx.GetProcAddress(addr as *const _) as *const _ }
+        let c_addr = CString::new(addr.as_bytes()).unwrap();
+        let ptr =
+            unsafe { glx.GetProcAddress(c_addr.as_ptr() as *const _) as *const core::ffi::c_void };
+        if ptr.is_null() {
+            glx.get_symbol(addr)
+        } else {
+            ptr
+        }
     }
 
     #[inline]
     pub fn swap_buffers(&self) -> Result<(), ContextError> {
         let glx = GLX.as_ref().unwrap();
         unsafe {
-            glx.SwapBuffers(self.xconn.display as *mut _, self.drawable);
+            glx.SwapBuffers(self.xconn.display as *mut _, *self.drawable.lock());
         }
         if let Err(err) = self.xconn.check_errors() {
             Err(ContextError::OsError(format!("`glXSwapBuffers` failed: {:?}", err)))
@@ -186,28 +212,180 @@ impl Context {
         }
     }
 
+    /// Copies the damaged parts of the back buffer to the front buffer with
+    /// `GLX_MESA_copy_sub_buffer`, or swaps the buffers when it's missing or
+    /// no damage is given.
+    ///
+    /// Unlike a swap, the copy keeps the back buffer as it is and ignores
+    /// vsync.
+    #[inline]
+    pub fn swap_buffers_with_damage(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        if !self.swap_buffers_with_damage_supported() || rects.is_empty() {
+            return self.swap_buffers();
+        }
+
+        let drawable = self.drawable.lock();
+        for rect in rects {
+            unsafe {
+                self.extra_functions.0.CopySubBufferMESA(
+                    self.xconn.display as *mut _,
+                    *drawable,
+                    rect.x as raw::c_int,
+                    rect.y as raw::c_int,
+                    rect.width as raw::c_int,
+                    rect.height as raw::c_int,
+                );
+            }
+        }
+        if let Err(err) = self.xconn.check_errors() {
+            Err(ContextError::OsError(format!("`glXCopySubBufferMESA` failed: {:?}", err)))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn swap_buffers_with_damage_supported(&self) -> bool {
+        check_ext(&self.extensions, "GLX_MESA_copy_sub_buffer")
+            && self.extra_functions.0.CopySubBufferMESA.is_loaded()
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        if !check_ext(&self.extensions, "GLX_EXT_buffer_age") {
+            return None;
+        }
+
+        let glx = GLX.as_ref().unwrap();
+        let mut age = 0;
+        unsafe {
+            glx.QueryDrawable(
+                self.xconn.display as *mut _,
+                *self.drawable.lock(),
+                ffi::glx_extra::BACK_BUFFER_AGE_EXT as raw::c_int,
+                &mut age,
+            );
+        }
+        self.xconn.check_errors().ok().map(|_| age)
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         self.pixel_format.clone()
     }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    #[inline]
+    pub fn get_visual_infos(&self) -> &ffi::XVisualInfo {
+        &self.visual_infos
+    }
+
+    /// Must be called with the context current.
+    pub fn renderer_info(&self) -> RendererInfo {
+        let mut info = RendererInfo::from_gl(|addr| self.get_proc_address(addr));
+        let functions = &self.extra_functions.0;
+        if !check_ext(&self.extensions, "GLX_MESA_query_renderer")
+            || !functions.QueryCurrentRendererIntegerMESA.is_loaded()
+        {
+            return info;
+        }
+
+        let query = |attribute, values: &mut [raw::c_uint]| unsafe {
+            functions.QueryCurrentRendererIntegerMESA(attribute as raw::c_int, values.as_mut_ptr())
+                != 0
+        };
+        let mut values = [0; 3];
+        if query(ffi::glx_extra::RENDERER_VENDOR_ID_MESA, &mut values[..1]) {
+            info.vendor_id = Some(values[0]);
+        }
+        if query(ffi::glx_extra::RENDERER_DEVICE_ID_MESA, &mut values[..1]) {
+            info.device_id = Some(values[0]);
+        }
+        if query(ffi::glx_extra::RENDERER_VIDEO_MEMORY_MESA, &mut values[..1]) {
+            info.video_memory = Some(values[0]);
+        }
+        if query(ffi::glx_extra::RENDERER_ACCELERATED_MESA, &mut values[..1]) {
+            info.software = values[0] == 0;
+        }
+        if query(ffi::glx_extra::RENDERER_VERSION_MESA, &mut values) {
+            info.driver_version = format!("{}.{}.{}", values[0], values[1], values[2]);
+        }
+        info
+    }
+
+    /// Replaces the pbuffer with one of the given size. The context, and so
+    /// every object created with it, is kept.
+    ///
+    /// If the context is current on this thread, the new pbuffer is made
+    /// current in place of the old one. On failure, the old pbuffer is kept.
+    pub fn resize_pbuffer(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        let glx = GLX.as_ref().unwrap();
+        let fb_config = match self.pbuffer_config {
+            Some(fb_config) => fb_config,
+            None => return Err(ContextError::OsError("not a pbuffer context".to_string())),
+        };
+        let mut drawable = self.drawable.lock();
+        let attributes: Vec<raw::c_int> = vec![
+            ffi::glx::PBUFFER_WIDTH as raw::c_int,
+            width as raw::c_int,
+            ffi::glx::PBUFFER_HEIGHT as raw::c_int,
+            height as raw::c_int,
+            0,
+        ];
+
+        unsafe {
+            let pbuffer =
+                glx.CreatePbuffer(self.xconn.display as *mut _, fb_config, attributes.as_ptr());
+            if let Err(err) = self.xconn.check_errors() {
+                return Err(ContextError::OsError(format!("`glXCreatePbuffer` failed: {:?}", err)));
+            }
+            if self.is_current() {
+                let res = glx.MakeCurrent(self.xconn.display as *mut _, pbuffer, self.context);
+                if res == 0 {
+                    let err = self.xconn.check_errors();
+                    glx.DestroyPbuffer(self.xconn.display as *mut _, pbuffer);
+                    return Err(ContextError::OsError(format!(
+                        "`glXMakeCurrent` failed: {:?}",
+                        err
+                    )));
+                }
+            }
+            glx.DestroyPbuffer(self.xconn.display as *mut _, *drawable);
+            *drawable = pbuffer;
+        }
+        Ok(())
+    }
 }
 
 unsafe impl Send for Context {}
 unsafe impl Sync for Context {}
 
+struct ExtraFunctions(ffi::glx_extra::Glx);
+
+impl std::fmt::Debug for ExtraFunctions {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ExtraFunctions(...)")
+    }
+}
+
 impl Drop for Context {
     fn drop(&mut self) {
         let glx = GLX.as_ref().unwrap();
         unsafe {
             // See `drop` for `crate::api::egl::Context` for rationale.
-            let mut guard = MakeCurrentGuard::new(&self.xconn, self.drawable, self.context)
+            let mut guard = MakeCurrentGuard::new(&self.xconn, *self.drawable.lock(), self.context)
                 .map_err(|err| ContextError::OsError(err))
                 .unwrap();
 
             let gl_finish_fn = self.get_proc_address("glFinish");
-            assert!(gl_finish_fn != std::ptr::null());
-            let gl_finish_fn = std::mem::transmute::<_, extern "system" fn()>(gl_finish_fn);
-            gl_finish_fn();
+            if !gl_finish_fn.is_null() {
+                let gl_finish_fn = std::mem::transmute::<_, extern "system" fn()>(gl_finish_fn);
+                gl_finish_fn();
+            }
 
             if guard.old_context() == Some(self.context) {
                 guard.invalidate()
@@ -215,13 +393,17 @@ impl Drop for Context {
             std::mem::drop(guard);
 
             glx.DestroyContext(self.xconn.display as *mut _, self.context);
+
+            if self.pbuffer_config.is_some() {
+                glx.DestroyPbuffer(self.xconn.display as *mut _, *self.drawable.lock());
+            }
         }
     }
 }
 
 #[derive(Debug)]
 pub struct ContextPrototype<'a> {
-    extensions: String,
+    extensions: Vec<String>,
     xconn: Arc<XConnection>,
     opengl: &'a GlAttributes<&'a Context>,
     fb_config: ffi::glx::types::GLXFBConfig,
@@ -236,7 +418,9 @@ impl<'a> ContextPrototype<'a> {
     }
 
     // creating GL context
-    fn create_context(&self) -> Result<(ffi::glx_extra::Glx, ffi::GLXContext), CreationError> {
+    fn create_context(
+        &self,
+    ) -> Result<(ffi::glx_extra::Glx, ffi::GLXContext, Option<(u8, u8)>), CreationError> {
         let glx = GLX.as_ref().unwrap();
         let share = match self.opengl.sharing {
             Some(ctx) => ctx.context,
@@ -249,82 +433,34 @@ impl<'a> ContextPrototype<'a> {
             unsafe { glx.GetProcAddress(c_str.as_ptr() as *const u8) as *const _ }
         });
 
-        let context = match self.opengl.version {
+        let opengl_versions = match self.opengl.version {
             GlRequest::Latest => {
-                let opengl_versions = [
-                    (4, 6),
-                    (4, 5),
-                    (4, 4),
-                    (4, 3),
-                    (4, 2),
-                    (4, 1),
-                    (4, 0),
-                    (3, 3),
-                    (3, 2),
-                    (3, 1),
-                ];
-                let ctx;
-                'outer: loop {
-                    // Try all OpenGL versions in descending order because some
-                    // non-compliant drivers don't return
-                    // the latest supported version but the one requested
-                    for opengl_version in opengl_versions.iter() {
-                        match create_context(
-                            &extra_functions,
-                            &self.extensions,
-                            &self.xconn.xlib,
-                            *opengl_version,
-                            self.opengl.profile,
-                            self.opengl.debug,
-                            self.opengl.robustness,
-                            share,
-                            self.xconn.display,
-                            self.fb_config,
-                            &self.visual_infos,
-                        ) {
-                            Ok(x) => {
-                                ctx = x;
-                                break 'outer;
-                            }
-                            Err(_) => continue,
-                        }
-                    }
-                    ctx = create_context(
-                        &extra_functions,
-                        &self.extensions,
-                        &self.xconn.xlib,
-                        (1, 0),
-                        self.opengl.profile,
-                        self.opengl.debug,
-                        self.opengl.robustness,
-                        share,
-                        self.xconn.display,
-                        self.fb_config,
-                        &self.visual_infos,
-                    )?;
-                    break;
-                }
-                ctx
+                let mut versions = Api::OpenGl.versions_in(&((3, 1)..=(4, 6)));
+                versions.push((1, 0));
+                versions
             }
-            GlRequest::Specific(Api::OpenGl, (major, minor)) => create_context(
-                &extra_functions,
-                &self.extensions,
-                &self.xconn.xlib,
-                (major, minor),
-                self.opengl.profile,
-                self.opengl.debug,
-                self.opengl.robustness,
-                share,
-                self.xconn.display,
-                self.fb_config,
-                &self.visual_infos,
-            )?,
+            GlRequest::Specific(Api::OpenGl, version) => vec![version],
             GlRequest::Specific(_, _) => panic!("Only OpenGL is supported"),
-            GlRequest::GlThenGles { opengl_version: (major, minor), .. } => create_context(
+            GlRequest::GlThenGles { opengl_version, .. } => vec![opengl_version],
+            // the OpenGL ES entries are left to EGL, which the X11 backend
+            // falls back to if none of these can be created
+            GlRequest::Ranges(ref ranges) => ranges
+                .iter()
+                .filter(|(api, _)| *api == Api::OpenGl)
+                .flat_map(|(api, range)| api.versions_in(range))
+                .collect(),
+        };
+
+        // Try all OpenGL versions in descending order because some
+        // non-compliant drivers don't return the latest supported version but
+        // the one requested
+        let mut result = Err(CreationError::OpenGlVersionNotSupported);
+        for &opengl_version in &opengl_versions {
+            result = create_context(
                 &extra_functions,
                 &self.extensions,
                 &self.xconn.xlib,
-                (major, minor),
+                opengl_version,
                 self.opengl.profile,
                 self.opengl.debug,
                 self.opengl.robustness,
@@ -332,16 +468,26 @@ impl<'a> ContextPrototype<'a> {
                 self.xconn.display,
                 self.fb_config,
                 &self.visual_infos,
-            )?,
-        };
+            )
+            .map(|context| (context, opengl_version));
+            if result.is_ok() {
+                break;
+            }
+        }
+        let (context, version) = result?;
+
+        // without GLX_ARB_create_context the version can't be requested at all
+        let version =
+            Some(version).filter(|_| check_ext(&self.extensions, "GLX_ARB_create_context"));
 
-        Ok((extra_functions, context))
+        Ok((extra_functions, context, version))
     }
 
+    #[cfg_attr(not(feature = "winit"), allow(dead_code))]
     pub fn finish_pbuffer(self, size: dpi::PhysicalSize<u32>) -> Result<Context, CreationError> {
         let glx = GLX.as_ref().unwrap();
         let size: (u32, u32) = size.into();
-        let (_extra_functions, context) = self.create_context()?;
+        let (extra_functions, context, version) = self.create_context()?;
 
         let attributes: Vec<raw::c_int> = vec![
             ffi::glx::PBUFFER_WIDTH as raw::c_int,
@@ -357,15 +503,20 @@ impl<'a> ContextPrototype<'a> {
 
         Ok(Context {
             xconn: self.xconn,
-            drawable: pbuffer,
+            drawable: Mutex::new(pbuffer),
+            pbuffer_config: Some(self.fb_config),
             context,
+            extensions: self.extensions,
+            extra_functions: ExtraFunctions(extra_functions),
+            version,
             pixel_format: self.pixel_format,
+            visual_infos: self.visual_infos,
         })
     }
 
     pub fn finish(self, window: ffi::Window) -> Result<Context, CreationError> {
         let glx = GLX.as_ref().unwrap();
-        let (extra_functions, context) = self.create_context()?;
+        let (extra_functions, context, version) = self.create_context()?;
 
         // vsync
         let swap_mode = if self.opengl.vsync { 1 } else { 0 };
@@ -417,9 +568,14 @@ impl<'a> ContextPrototype<'a> {
 
         Ok(Context {
             xconn: self.xconn,
-            drawable: window,
+            drawable: Mutex::new(window),
+            pbuffer_config: None,
             context,
+            extensions: self.extensions,
+            extra_functions: ExtraFunctions(extra_functions),
+            version,
             pixel_format: self.pixel_format,
+            visual_infos: self.visual_infos,
         })
     }
 }
@@ -430,7 +586,7 @@ extern "C" fn x_error_callback(_dpy: *mut ffi::Display, _err: *mut ffi::XErrorEv
 
 fn create_context(
     extra_functions: &ffi::glx_extra::Glx,
-    extensions: &str,
+    extensions: &[String],
     xlib: &ffi::Xlib,
     version: (u8, u8),
     profile: Option<GlProfile>,
@@ -540,9 +696,22 @@ fn create_context(
     }
 }
 
+/// Converts the class of an X visual to the matching `GLX_X_VISUAL_TYPE`.
+fn x_visual_type(class: raw::c_int) -> Result<ffi::glx::types::GLenum, CreationError> {
+    Ok(match class {
+        ffi::StaticGray => ffi::glx::STATIC_GRAY,
+        ffi::GrayScale => ffi::glx::GRAY_SCALE,
+        ffi::StaticColor => ffi::glx::STATIC_COLOR,
+        ffi::PseudoColor => ffi::glx::PSEUDO_COLOR,
+        ffi::TrueColor => ffi::glx::TRUE_COLOR,
+        ffi::DirectColor => ffi::glx::DIRECT_COLOR,
+        _ => return Err(CreationError::NoAvailablePixelFormat),
+    })
+}
+
 /// Enumerates all available FBConfigs
 unsafe fn choose_fbconfig(
-    extensions: &str,
+    extensions: &[String],
     xconn: &Arc<XConnection>,
     screen_id: raw::c_int,
     pf_reqs: &PixelFormatRequirements,
@@ -557,18 +726,19 @@ unsafe fn choose_fbconfig(
         out.push(ffi::glx::X_RENDERABLE as raw::c_int);
         out.push(1);
 
-        if let Some(xid) = pf_reqs.x11_visual_xid {
+        if let Some(xid) = pf_reqs.x11.visual_xid {
             // getting the visual infos
             let fvi = crate::platform_impl::x11_utils::get_visual_info_from_xid(&xconn, xid);
 
             out.push(ffi::glx::X_VISUAL_TYPE as raw::c_int);
-            out.push(fvi.class as raw::c_int);
+            out.push(x_visual_type(fvi.class)? as raw::c_int);
 
             out.push(ffi::glx::VISUAL_ID as raw::c_int);
             out.push(xid as raw::c_int);
         } else {
+            let class = pf_reqs.x11.visual_class.unwrap_or(ffi::TrueColor);
             out.push(ffi::glx::X_VISUAL_TYPE as raw::c_int);
-            out.push(ffi::glx::TRUE_COLOR as raw::c_int);
+            out.push(x_visual_type(class)? as raw::c_int);
         }
 
         out.push(ffi::glx::DRAWABLE_TYPE as raw::c_int);
@@ -738,20 +908,28 @@ unsafe fn choose_fbconfig(
         },
         srgb: get_attrib(ffi::glx_extra::FRAMEBUFFER_SRGB_CAPABLE_ARB as raw::c_int) != 0
             || get_attrib(ffi::glx_extra::FRAMEBUFFER_SRGB_CAPABLE_EXT as raw::c_int) != 0,
+        transparency: surface_type == SurfaceType::Window
+            && crate::platform_impl::x11_utils::examine_visual_info(
+                &xconn,
+                visual_infos,
+                true,
+                None,
+            )
+            .is_ok(),
     };
 
     Ok((fb_config, pf_desc, visual_infos))
 }
 
 /// Checks if `ext` is available.
-fn check_ext(extensions: &str, ext: &str) -> bool {
-    extensions.split(' ').find(|&s| s == ext).is_some()
+fn check_ext(extensions: &[String], ext: &str) -> bool {
+    extensions.iter().any(|s| s == ext)
 }
 
 fn load_extensions(
     xconn: &Arc<XConnection>,
     screen_id: raw::c_int,
-) -> Result<String, CreationError> {
+) -> Result<Vec<String>, CreationError> {
     unsafe {
         let glx = GLX.as_ref().unwrap();
         let extensions = glx.QueryExtensionsString(xconn.display as *mut _, screen_id);
@@ -760,7 +938,7 @@ fn load_extensions(
                 "`glXQueryExtensionsString` found no glX extensions".to_string(),
             ));
         }
-        let extensions = CStr::from_ptr(extensions).to_bytes().to_vec();
-        Ok(String::from_utf8(extensions).unwrap())
+        let extensions = CStr::from_ptr(extensions).to_string_lossy();
+        Ok(extensions.split(' ').filter(|ext| !ext.is_empty()).map(String::from).collect())
     }
 }
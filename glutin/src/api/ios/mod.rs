@@ -62,13 +62,14 @@
 
 use crate::platform::ios::{WindowBuilderExtIOS, WindowExtIOS};
 use crate::{
-    Api, ContextError, CreationError, GlAttributes, GlRequest, PixelFormat,
-    PixelFormatRequirements, Rect,
+    Api, ContextError, ContextPriority, CreationError, GlAttributes, GlRequest, PixelFormat,
+    PixelFormatRequirements, Rect, RendererInfo,
 };
 
 use glutin_gles2_sys as ffi;
 use objc::declare::ClassDecl;
 use objc::runtime::{Class, Object, Sel, BOOL, NO, YES};
+use raw_window_handle::RawWindowHandle;
 use winit::dpi;
 use winit::event_loop::EventLoopWindowTarget;
 use winit::window::WindowBuilder;
@@ -179,7 +180,7 @@ impl Context {
         if gl_attrs.sharing.is_some() {
             unimplemented!("Shared contexts are unimplemented on iOS.");
         }
-        let version = match gl_attrs.version {
+        let version = match gl_attrs.version.highest_specific() {
             GlRequest::Latest => ffi::kEAGLRenderingAPIOpenGLES3,
             GlRequest::Specific(api, (major, _minor)) => {
                 if api == Api::OpenGlEs {
@@ -206,6 +207,18 @@ impl Context {
         Ok((win, context))
     }
 
+    #[inline]
+    pub unsafe fn new_from_raw_window_handle(
+        _handle: RawWindowHandle,
+        _size: dpi::PhysicalSize<u32>,
+        _pf_reqs: &PixelFormatRequirements,
+        _gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        Err(CreationError::NotSupported(
+            "building from a raw window handle is not supported on this platform".to_string(),
+        ))
+    }
+
     #[inline]
     pub fn new_headless<T>(
         el: &EventLoopWindowTarget<T>,
@@ -315,6 +328,36 @@ impl Context {
         false
     }
 
+    #[inline]
+    pub fn set_damage_region(&self, _rects: &[Rect]) -> Result<(), ContextError> {
+        Err(ContextError::FunctionUnavailable)
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        &[]
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        None
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        RendererInfo::from_gl(|addr| self.get_proc_address(addr))
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         let color_format = ColorFormat::for_view(self.view);
@@ -328,12 +371,14 @@ impl Context {
             double_buffer: true,
             multisampling: multisampling_for_view(self.view),
             srgb: color_format.srgb(),
+            transparency: false,
         }
     }
 
     #[inline]
-    pub fn resize(&self, _width: u32, _height: u32) {
+    pub fn resize(&self, _width: u32, _height: u32) -> Result<(), ContextError> {
         // N/A
+        Ok(())
     }
 
     #[inline]
@@ -397,6 +442,11 @@ impl Context {
     pub fn get_api(&self) -> Api {
         Api::OpenGlEs
     }
+
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        None
+    }
 }
 
 fn create_view_class() {
@@ -0,0 +1,169 @@
+#![cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+#![cfg(feature = "mock")]
+
+use crate::mock::{MockBackend, MockCall, MockOp};
+use crate::{
+    Api, ContextError, ContextPriority, CreationError, GlAttributes, GlRequest, PixelFormat,
+    PixelFormatRequirements, Rect, RendererInfo,
+};
+
+use crate::dpi;
+
+use std::cell::Cell;
+
+thread_local! {
+    // the mock context current on this thread, as the address of its backend
+    // and its id in the backend
+    static CURRENT: Cell<Option<(usize, u64)>> = const { Cell::new(None) };
+}
+
+/// A context without any GL stack behind it, recording its calls into a
+/// [`MockBackend`].
+#[derive(Debug)]
+pub struct MockContext {
+    id: u64,
+    backend: MockBackend,
+    api: Api,
+    version: Option<(u8, u8)>,
+    priority: Option<ContextPriority>,
+    pixel_format: PixelFormat,
+}
+
+impl MockContext {
+    pub fn new<T>(
+        pf_reqs: &PixelFormatRequirements,
+        opengl: &GlAttributes<T>,
+        _size: dpi::PhysicalSize<u32>,
+        backend: MockBackend,
+    ) -> Result<Self, CreationError> {
+        let id = backend.create()?;
+
+        // pretend the first version requested was available
+        let (api, version) = match opengl.version {
+            GlRequest::Latest => (Api::OpenGl, None),
+            GlRequest::Specific(api, version) => (api, Some(version)),
+            GlRequest::GlThenGles { opengl_version, .. } => (Api::OpenGl, Some(opengl_version)),
+            GlRequest::Ranges(ref ranges) => ranges
+                .iter()
+                .find_map(|(api, range)| api.versions_in(range).first().map(|&v| (*api, Some(v))))
+                .ok_or(CreationError::OpenGlVersionNotSupported)?,
+        };
+
+        let pixel_format = PixelFormat {
+            hardware_accelerated: pf_reqs.hardware_accelerated.unwrap_or(true),
+            color_bits: pf_reqs.color_bits.unwrap_or(24),
+            alpha_bits: pf_reqs.alpha_bits.unwrap_or(8),
+            depth_bits: pf_reqs.depth_bits.unwrap_or(24),
+            stencil_bits: pf_reqs.stencil_bits.unwrap_or(8),
+            stereoscopy: pf_reqs.stereoscopy,
+            double_buffer: pf_reqs.double_buffer.unwrap_or(true),
+            multisampling: pf_reqs.multisampling,
+            srgb: pf_reqs.srgb,
+            transparency: false,
+        };
+
+        Ok(MockContext { id, backend, api, version, priority: opengl.priority, pixel_format })
+    }
+
+    #[inline]
+    pub unsafe fn make_current(&self) -> Result<(), ContextError> {
+        self.backend.call(self.id, MockCall::MakeCurrent, MockOp::MakeCurrent)?;
+        CURRENT.with(|current| current.set(Some(self.key())));
+        Ok(())
+    }
+
+    #[inline]
+    pub unsafe fn make_not_current(&self) -> Result<(), ContextError> {
+        self.backend.call(self.id, MockCall::MakeNotCurrent, MockOp::MakeNotCurrent)?;
+        if self.is_current() {
+            CURRENT.with(|current| current.set(None));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_current(&self) -> bool {
+        CURRENT.with(|current| current.get() == Some(self.key()))
+    }
+
+    // identifies the context among the ones of every backend
+    fn key(&self) -> (usize, u64) {
+        (self.backend.addr(), self.id)
+    }
+
+    #[inline]
+    pub fn get_api(&self) -> Api {
+        self.api
+    }
+
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        self.version
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        self.priority
+    }
+
+    #[inline]
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        self.backend.call(self.id, MockCall::Resize { width, height }, MockOp::Resize)
+    }
+
+    #[inline]
+    pub fn get_proc_address(&self, _addr: &str) -> *const core::ffi::c_void {
+        std::ptr::null()
+    }
+
+    #[inline]
+    pub fn swap_buffers(&self) -> Result<(), ContextError> {
+        self.backend.call(self.id, MockCall::SwapBuffers, MockOp::SwapBuffers)
+    }
+
+    #[inline]
+    pub fn swap_buffers_with_damage(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        let call = MockCall::SwapBuffersWithDamage(rects.to_vec());
+        self.backend.call(self.id, call, MockOp::SwapBuffers)
+    }
+
+    #[inline]
+    pub fn swap_buffers_with_damage_supported(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        RendererInfo {
+            vendor: "glutin".to_string(),
+            renderer: "mock".to_string(),
+            driver_version: String::new(),
+            vendor_id: None,
+            device_id: None,
+            video_memory: None,
+            software: true,
+        }
+    }
+
+    #[inline]
+    pub fn get_pixel_format(&self) -> PixelFormat {
+        self.pixel_format.clone()
+    }
+}
+
+impl Drop for MockContext {
+    fn drop(&mut self) {
+        if self.is_current() {
+            CURRENT.with(|current| current.set(None));
+        }
+        self.backend.record(MockCall::Destroy)
+    }
+}
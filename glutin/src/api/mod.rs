@@ -3,5 +3,6 @@ pub mod dlloader;
 pub mod egl;
 pub mod glx;
 pub mod ios;
+pub mod mock;
 pub mod osmesa;
 pub mod wgl;
@@ -12,10 +12,11 @@ pub mod ffi {
 
 use crate::{
     Api, ContextError, CreationError, GlAttributes, GlProfile, GlRequest, PixelFormatRequirements,
-    Robustness,
+    RendererInfo, Robustness,
 };
 
-use winit::dpi;
+use crate::dpi;
+use parking_lot::Mutex;
 
 use std::ffi::CString;
 use std::os::raw;
@@ -23,11 +24,54 @@ use std::os::raw;
 #[derive(Debug)]
 pub struct OsMesaContext {
     context: osmesa_sys::OSMesaContext,
+    framebuffer: Mutex<Framebuffer>,
+    version: Option<(u8, u8)>,
+    // whether the buffer is BGRA with its first row at the top, to be shown
+    // in a window
+    top_down: bool,
+}
+
+#[derive(Debug)]
+struct Framebuffer {
     buffer: Vec<u32>,
     width: u32,
     height: u32,
 }
 
+impl Framebuffer {
+    fn new(width: u32, height: u32) -> Self {
+        Framebuffer { buffer: vec![0; (width * height) as usize], width, height }
+    }
+
+    unsafe fn make_current(
+        &self,
+        context: osmesa_sys::OSMesaContext,
+        top_down: bool,
+    ) -> Result<(), ContextError> {
+        let ret = osmesa_sys::OSMesaMakeCurrent(
+            context,
+            self.buffer.as_ptr() as *mut _,
+            0x1401,
+            self.width as raw::c_int,
+            self.height as raw::c_int,
+        );
+
+        // an error can only happen in case of invalid parameter, such as an
+        // empty buffer
+        if ret == 0 {
+            return Err(ContextError::OsError(format!(
+                "OSMesaMakeCurrent failed with a {}x{} buffer",
+                self.width, self.height
+            )));
+        }
+
+        if top_down {
+            osmesa_sys::OSMesaPixelStore(osmesa_sys::OSMESA_Y_UP, 0);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct NoEsOrWebGlSupported;
 
@@ -66,9 +110,30 @@ impl std::error::Error for LoadingError {
 
 impl OsMesaContext {
     pub fn new(
-        _pf_reqs: &PixelFormatRequirements,
+        pf_reqs: &PixelFormatRequirements,
         opengl: &GlAttributes<&OsMesaContext>,
         size: dpi::PhysicalSize<u32>,
+    ) -> Result<Self, CreationError> {
+        Self::new_impl(pf_reqs, opengl, size, false)
+    }
+
+    /// Creates a context rendering into a BGRA buffer whose first row is the
+    /// top one, as windows expect, with the depth and stencil buffers of
+    /// `pf_reqs`.
+    #[cfg_attr(not(feature = "winit"), allow(dead_code))]
+    pub fn new_windowed(
+        pf_reqs: &PixelFormatRequirements,
+        opengl: &GlAttributes<&OsMesaContext>,
+        size: dpi::PhysicalSize<u32>,
+    ) -> Result<Self, CreationError> {
+        Self::new_impl(pf_reqs, opengl, size, true)
+    }
+
+    fn new_impl(
+        pf_reqs: &PixelFormatRequirements,
+        opengl: &GlAttributes<&OsMesaContext>,
+        size: dpi::PhysicalSize<u32>,
+        top_down: bool,
     ) -> Result<Self, CreationError> {
         osmesa_sys::OsMesa::try_loading()
             .map_err(LoadingError::new)
@@ -85,82 +150,106 @@ impl OsMesaContext {
             _ => (),
         }
 
-        // TODO: use `pf_reqs` for the format
+        // TODO: use `pf_reqs` for the format of offscreen contexts too
 
-        let mut attribs = Vec::new();
-
-        if let Some(profile) = opengl.profile {
-            attribs.push(osmesa_sys::OSMESA_PROFILE);
-
-            match profile {
-                GlProfile::Compatibility => {
-                    attribs.push(osmesa_sys::OSMESA_COMPAT_PROFILE);
+        let versions = match opengl.version {
+            GlRequest::Latest => vec![None],
+            GlRequest::Specific(Api::OpenGl, version) => vec![Some(version)],
+            GlRequest::Specific(Api::OpenGlEs, _) | GlRequest::Specific(Api::WebGl, _) => {
+                return Err(CreationError::NoBackendAvailable(Box::new(NoEsOrWebGlSupported)));
+            }
+            GlRequest::GlThenGles { opengl_version, .. } => vec![Some(opengl_version)],
+            GlRequest::Ranges(ref ranges) => {
+                let versions: Vec<_> = ranges
+                    .iter()
+                    .filter(|(api, _)| *api == Api::OpenGl)
+                    .flat_map(|(api, range)| api.versions_in(range))
+                    .map(Some)
+                    .collect();
+                if versions.is_empty() {
+                    return Err(CreationError::NoBackendAvailable(Box::new(NoEsOrWebGlSupported)));
                 }
-                GlProfile::Core => {
-                    attribs.push(osmesa_sys::OSMESA_CORE_PROFILE);
+                versions
+            }
+        };
+
+        let mut context = std::ptr::null_mut();
+        let mut version = None;
+        for &attempt in &versions {
+            let mut attribs = Vec::new();
+
+            if let Some(profile) = opengl.profile {
+                attribs.push(osmesa_sys::OSMESA_PROFILE);
+
+                match profile {
+                    GlProfile::Compatibility => {
+                        attribs.push(osmesa_sys::OSMESA_COMPAT_PROFILE);
+                    }
+                    GlProfile::Core => {
+                        attribs.push(osmesa_sys::OSMESA_CORE_PROFILE);
+                    }
                 }
             }
-        }
 
-        match opengl.version {
-            GlRequest::Latest => {}
-            GlRequest::Specific(Api::OpenGl, (major, minor)) => {
-                attribs.push(osmesa_sys::OSMESA_CONTEXT_MAJOR_VERSION);
-                attribs.push(major as raw::c_int);
-                attribs.push(osmesa_sys::OSMESA_CONTEXT_MINOR_VERSION);
-                attribs.push(minor as raw::c_int);
+            if top_down {
+                attribs.push(osmesa_sys::OSMESA_FORMAT);
+                attribs.push(osmesa_sys::OSMESA_BGRA as raw::c_int);
+                attribs.push(osmesa_sys::OSMESA_DEPTH_BITS);
+                attribs.push(pf_reqs.depth_bits.unwrap_or(0) as raw::c_int);
+                attribs.push(osmesa_sys::OSMESA_STENCIL_BITS);
+                attribs.push(pf_reqs.stencil_bits.unwrap_or(0) as raw::c_int);
             }
-            GlRequest::Specific(Api::OpenGlEs, _) | GlRequest::Specific(Api::WebGl, _) => {
-                return Err(CreationError::NoBackendAvailable(Box::new(NoEsOrWebGlSupported)));
-            }
-            GlRequest::GlThenGles { opengl_version: (major, minor), .. } => {
+
+            if let Some((major, minor)) = attempt {
                 attribs.push(osmesa_sys::OSMESA_CONTEXT_MAJOR_VERSION);
                 attribs.push(major as raw::c_int);
                 attribs.push(osmesa_sys::OSMESA_CONTEXT_MINOR_VERSION);
                 attribs.push(minor as raw::c_int);
             }
-        }
 
-        // attribs array must be NULL terminated.
-        attribs.push(0);
+            // attribs array must be NULL terminated.
+            attribs.push(0);
+
+            context = unsafe {
+                osmesa_sys::OSMesaCreateContextAttribs(attribs.as_ptr(), std::ptr::null_mut())
+            };
+            if !context.is_null() {
+                version = attempt;
+                break;
+            }
+        }
+        if context.is_null() {
+            return Err(CreationError::OsError("OSMesaCreateContextAttribs failed".to_string()));
+        }
 
         let size: (u32, u32) = size.into();
 
         Ok(OsMesaContext {
-            width: size.0,
-            height: size.1,
-            buffer: std::iter::repeat(unsafe { std::mem::zeroed() })
-                .take((size.0 * size.1) as usize)
-                .collect(),
-            context: unsafe {
-                let ctx =
-                    osmesa_sys::OSMesaCreateContextAttribs(attribs.as_ptr(), std::ptr::null_mut());
-                if ctx.is_null() {
-                    return Err(CreationError::OsError(
-                        "OSMesaCreateContextAttribs failed".to_string(),
-                    ));
-                }
-                ctx
-            },
+            framebuffer: Mutex::new(Framebuffer::new(size.0, size.1)),
+            context,
+            version,
+            top_down,
         })
     }
 
     #[inline]
     pub unsafe fn make_current(&self) -> Result<(), ContextError> {
-        let ret = osmesa_sys::OSMesaMakeCurrent(
-            self.context,
-            self.buffer.as_ptr() as *mut _,
-            0x1401,
-            self.width as raw::c_int,
-            self.height as raw::c_int,
-        );
+        self.framebuffer.lock().make_current(self.context, self.top_down)
+    }
 
-        // an error can only happen in case of invalid parameter, which would
-        // indicate a bug in glutin
-        if ret == 0 {
-            panic!("OSMesaMakeCurrent failed");
+    /// Reallocates the buffer rendered into with the given size, keeping the
+    /// context. Its previous content is lost.
+    ///
+    /// If the context is current and can't be made current with the new
+    /// buffer, the previous one is kept.
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        let mut framebuffer = self.framebuffer.lock();
+        let new_framebuffer = Framebuffer::new(width, height);
+        if self.is_current() {
+            // on failure, the previous buffer stays bound
+            unsafe { new_framebuffer.make_current(self.context, self.top_down)? }
         }
-
+        *framebuffer = new_framebuffer;
         Ok(())
     }
 
@@ -189,6 +278,13 @@ impl OsMesaContext {
         Ok(())
     }
 
+    /// Calls `f` with the pixels rendered into, and their width and height.
+    #[cfg_attr(not(feature = "winit"), allow(dead_code))]
+    pub fn with_buffer<R, F: FnOnce(&[u32], u32, u32) -> R>(&self, f: F) -> R {
+        let framebuffer = self.framebuffer.lock();
+        f(&framebuffer.buffer, framebuffer.width, framebuffer.height)
+    }
+
     #[inline]
     pub fn is_current(&self) -> bool {
         unsafe { osmesa_sys::OSMesaGetCurrentContext() == self.context }
@@ -199,6 +295,17 @@ impl OsMesaContext {
         Api::OpenGl
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        self.version
+    }
+
+    /// Must be called with the context current.
+    pub fn renderer_info(&self) -> RendererInfo {
+        // OSMesa always renders on the CPU
+        RendererInfo { software: true, ..RendererInfo::from_gl(|addr| self.get_proc_address(addr)) }
+    }
+
     #[inline]
     pub unsafe fn raw_handle(&self) -> *mut raw::c_void {
         self.context as *mut _
@@ -39,6 +39,9 @@ pub struct Context {
 
     /// The pixel format that has been used to create this context.
     pixel_format: PixelFormat,
+
+    /// The WGL extensions supported by the device context.
+    extensions: Vec<String>,
 }
 
 /// A simple wrapper that destroys the window when it is destroyed.
@@ -104,9 +107,10 @@ impl Context {
         } else {
             format!("")
         };
+        let extensions: Vec<String> =
+            extensions.split(' ').filter(|ext| !ext.is_empty()).map(String::from).collect();
 
-        let use_arb_for_pixel_format =
-            extensions.split(' ').find(|&i| i == "WGL_ARB_pixel_format").is_some();
+        let use_arb_for_pixel_format = extensions.iter().any(|i| i == "WGL_ARB_pixel_format");
 
         // calling SetPixelFormat, if not already done
         let mut pixel_format_id = GetPixelFormat(hdc);
@@ -133,13 +137,13 @@ impl Context {
 
         // creating the OpenGL context
         let context =
-            create_context(Some((&extra_functions, pf_reqs, opengl, &extensions)), win, hdc)?;
+            create_context(Some((&extra_functions, pf_reqs, opengl, &extensions[..])), win, hdc)?;
 
         // loading the opengl32 module
         let gl_library = load_opengl32_dll()?;
 
         // handling vsync
-        if extensions.split(' ').find(|&i| i == "WGL_EXT_swap_control").is_some() {
+        if extensions.iter().any(|i| i == "WGL_EXT_swap_control") {
             let _guard = CurrentContextGuard::make_current(hdc, context.0)?;
 
             if extra_functions.SwapIntervalEXT(if opengl.vsync { 1 } else { 0 }) == 0 {
@@ -147,7 +151,7 @@ impl Context {
             }
         }
 
-        Ok(Context { context, hdc, gl_library, pixel_format })
+        Ok(Context { context, hdc, gl_library, pixel_format, extensions })
     }
 
     /// Returns the raw HGLRC.
@@ -210,10 +214,20 @@ impl Context {
         Api::OpenGl
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        None
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         self.pixel_format.clone()
     }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        &self.extensions
+    }
 }
 
 unsafe impl Send for Context {}
@@ -227,7 +241,7 @@ unsafe impl Sync for Context {}
 /// Otherwise, only the basic API will be used and the chances of
 /// `CreationError::NotSupported` being returned increase.
 unsafe fn create_context(
-    extra: Option<(&gl::wgl_extra::Wgl, &PixelFormatRequirements, &GlAttributes<HGLRC>, &str)>,
+    extra: Option<(&gl::wgl_extra::Wgl, &PixelFormatRequirements, &GlAttributes<HGLRC>, &[String])>,
     _: HWND,
     hdc: HDC,
 ) -> Result<ContextWrapper, CreationError> {
@@ -236,10 +250,10 @@ unsafe fn create_context(
     if let Some((extra_functions, _pf_reqs, opengl, extensions)) = extra {
         share = opengl.sharing.unwrap_or(std::ptr::null_mut());
 
-        if extensions.split(' ').find(|&i| i == "WGL_ARB_create_context").is_some() {
+        if extensions.iter().any(|i| i == "WGL_ARB_create_context") {
             let mut attributes = Vec::new();
 
-            match opengl.version {
+            match opengl.version.highest_specific() {
                 GlRequest::Latest => {}
                 GlRequest::Specific(Api::OpenGl, (major, minor)) => {
                     attributes.push(gl::wgl_extra::CONTEXT_MAJOR_VERSION_ARB as raw::c_int);
@@ -248,11 +262,7 @@ unsafe fn create_context(
                     attributes.push(minor as raw::c_int);
                 }
                 GlRequest::Specific(Api::OpenGlEs, (major, minor)) => {
-                    if extensions
-                        .split(' ')
-                        .find(|&i| i == "WGL_EXT_create_context_es2_profile")
-                        .is_some()
-                    {
+                    if extensions.iter().any(|i| i == "WGL_EXT_create_context_es2_profile") {
                         attributes.push(gl::wgl_extra::CONTEXT_PROFILE_MASK_ARB as raw::c_int);
                         attributes.push(gl::wgl_extra::CONTEXT_ES2_PROFILE_BIT_EXT as raw::c_int);
                     } else {
@@ -276,8 +286,7 @@ unsafe fn create_context(
             }
 
             if let Some(profile) = opengl.profile {
-                if extensions.split(' ').find(|&i| i == "WGL_ARB_create_context_profile").is_some()
-                {
+                if extensions.iter().any(|i| i == "WGL_ARB_create_context_profile") {
                     let flag = match profile {
                         GlProfile::Compatibility => {
                             gl::wgl_extra::CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB
@@ -298,11 +307,7 @@ unsafe fn create_context(
                 let mut flags = 0;
 
                 // robustness
-                if extensions
-                    .split(' ')
-                    .find(|&i| i == "WGL_ARB_create_context_robustness")
-                    .is_some()
-                {
+                if extensions.iter().any(|i| i == "WGL_ARB_create_context_robustness") {
                     match opengl.robustness {
                         Robustness::RobustNoResetNotification
                         | Robustness::TryRobustNoResetNotification => {
@@ -508,6 +513,7 @@ unsafe fn choose_native_pixel_format(
         double_buffer: (output.dwFlags & PFD_DOUBLEBUFFER) != 0,
         multisampling: None,
         srgb: false,
+        transparency: false,
     };
 
     if pf_desc.alpha_bits < pf_reqs.alpha_bits.unwrap_or(0) {
@@ -541,7 +547,7 @@ unsafe fn choose_native_pixel_format(
 /// Gives more precise results than `enumerate_native_pixel_formats`.
 unsafe fn choose_arb_pixel_format_id(
     extra: &gl::wgl_extra::Wgl,
-    extensions: &str,
+    extensions: &[String],
     hdc: HDC,
     pf_reqs: &PixelFormatRequirements,
 ) -> Result<raw::c_int, ()> {
@@ -556,7 +562,7 @@ unsafe fn choose_arb_pixel_format_id(
 
         out.push(gl::wgl_extra::PIXEL_TYPE_ARB as raw::c_int);
         if pf_reqs.float_color_buffer {
-            if extensions.split(' ').find(|&i| i == "WGL_ARB_pixel_format_float").is_some() {
+            if extensions.iter().any(|i| i == "WGL_ARB_pixel_format_float") {
                 out.push(gl::wgl_extra::TYPE_RGBA_FLOAT_ARB as raw::c_int);
             } else {
                 return Err(());
@@ -601,7 +607,7 @@ unsafe fn choose_arb_pixel_format_id(
         out.push(if double_buffer { 1 } else { 0 });
 
         if let Some(multisampling) = pf_reqs.multisampling {
-            if extensions.split(' ').find(|&i| i == "WGL_ARB_multisample").is_some() {
+            if extensions.iter().any(|i| i == "WGL_ARB_multisample") {
                 out.push(gl::wgl_extra::SAMPLE_BUFFERS_ARB as raw::c_int);
                 out.push(if multisampling == 0 { 0 } else { 1 });
                 out.push(gl::wgl_extra::SAMPLES_ARB as raw::c_int);
@@ -616,10 +622,10 @@ unsafe fn choose_arb_pixel_format_id(
 
         // WGL_*_FRAMEBUFFER_SRGB might be assumed to be true if not listed;
         // so it's best to list it out and set its value as necessary.
-        if extensions.split(' ').find(|&i| i == "WGL_ARB_framebuffer_sRGB").is_some() {
+        if extensions.iter().any(|i| i == "WGL_ARB_framebuffer_sRGB") {
             out.push(gl::wgl_extra::FRAMEBUFFER_SRGB_CAPABLE_ARB as raw::c_int);
             out.push(pf_reqs.srgb as raw::c_int);
-        } else if extensions.split(' ').find(|&i| i == "WGL_EXT_framebuffer_sRGB").is_some() {
+        } else if extensions.iter().any(|i| i == "WGL_EXT_framebuffer_sRGB") {
             out.push(gl::wgl_extra::FRAMEBUFFER_SRGB_CAPABLE_EXT as raw::c_int);
             out.push(pf_reqs.srgb as raw::c_int);
         } else if pf_reqs.srgb {
@@ -629,7 +635,7 @@ unsafe fn choose_arb_pixel_format_id(
         match pf_reqs.release_behavior {
             ReleaseBehavior::Flush => (),
             ReleaseBehavior::None => {
-                if extensions.split(' ').find(|&i| i == "WGL_ARB_context_flush_control").is_some() {
+                if extensions.iter().any(|i| i == "WGL_ARB_context_flush_control") {
                     out.push(gl::wgl_extra::CONTEXT_RELEASE_BEHAVIOR_ARB as raw::c_int);
                     out.push(gl::wgl_extra::CONTEXT_RELEASE_BEHAVIOR_NONE_ARB as raw::c_int);
                 }
@@ -663,7 +669,7 @@ unsafe fn choose_arb_pixel_format_id(
 
 unsafe fn choose_arb_pixel_format(
     extra: &gl::wgl_extra::Wgl,
-    extensions: &str,
+    extensions: &[String],
     hdc: HDC,
     format_id: raw::c_int,
 ) -> Result<PixelFormat, ()> {
@@ -692,7 +698,7 @@ unsafe fn choose_arb_pixel_format(
         stereoscopy: get_info(gl::wgl_extra::STEREO_ARB) != 0,
         double_buffer: get_info(gl::wgl_extra::DOUBLE_BUFFER_ARB) != 0,
         multisampling: {
-            if extensions.split(' ').find(|&i| i == "WGL_ARB_multisample").is_some() {
+            if extensions.iter().any(|i| i == "WGL_ARB_multisample") {
                 match get_info(gl::wgl_extra::SAMPLES_ARB) {
                     0 => None,
                     a => Some(a as u16),
@@ -701,13 +707,14 @@ unsafe fn choose_arb_pixel_format(
                 None
             }
         },
-        srgb: if extensions.split(' ').find(|&i| i == "WGL_ARB_framebuffer_sRGB").is_some() {
+        srgb: if extensions.iter().any(|i| i == "WGL_ARB_framebuffer_sRGB") {
             get_info(gl::wgl_extra::FRAMEBUFFER_SRGB_CAPABLE_ARB) != 0
-        } else if extensions.split(' ').find(|&i| i == "WGL_EXT_framebuffer_sRGB").is_some() {
+        } else if extensions.iter().any(|i| i == "WGL_EXT_framebuffer_sRGB") {
             get_info(gl::wgl_extra::FRAMEBUFFER_SRGB_CAPABLE_EXT) != 0
         } else {
             false
         },
+        transparency: false,
     };
 
     Ok(pf_desc)
@@ -1,6 +1,9 @@
 use super::*;
 
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "winit")]
 use winit::event_loop::EventLoopWindowTarget;
 
 /// Represents an OpenGL [`Context`].
@@ -14,6 +17,9 @@ use winit::event_loop::EventLoopWindowTarget;
 /// # Example
 ///
 /// ```no_run
+/// # #[cfg(not(feature = "winit"))]
+/// # fn main() {}
+/// # #[cfg(feature = "winit")]
 /// # fn main() {
 /// # let el = glutin::event_loop::EventLoop::new();
 /// # let wb = glutin::window::WindowBuilder::new();
@@ -32,7 +38,11 @@ use winit::event_loop::EventLoopWindowTarget;
 /// [`Context`]: struct.Context.html
 #[derive(Debug)]
 pub struct Context<T: ContextCurrentState> {
-    pub(crate) context: platform_impl::Context,
+    pub(crate) context: Arc<platform_impl::Context>,
+    // filled by the first `has_gl_extension`
+    pub(crate) gl_extensions: Mutex<Option<HashSet<String>>>,
+    // filled by `ProcLoader`, null addresses included
+    pub(crate) procs: Mutex<HashMap<String, usize>>,
     pub(crate) phantom: PhantomData<T>,
 }
 
@@ -43,8 +53,8 @@ impl<T: ContextCurrentState> Context<T> {
     /// struct.ContextWrapper.html#method.make_current
     pub unsafe fn make_current(self) -> Result<Context<PossiblyCurrent>, (Self, ContextError)> {
         match self.context.make_current() {
-            Ok(()) => Ok(Context { context: self.context, phantom: PhantomData }),
-            Err(err) => Err((Context { context: self.context, phantom: PhantomData }, err)),
+            Ok(()) => Ok(self.with_state()),
+            Err(err) => Err((self, err)),
         }
     }
 
@@ -54,8 +64,8 @@ impl<T: ContextCurrentState> Context<T> {
     /// struct.ContextWrapper.html#method.make_not_current
     pub unsafe fn make_not_current(self) -> Result<Context<NotCurrent>, (Self, ContextError)> {
         match self.context.make_not_current() {
-            Ok(()) => Ok(Context { context: self.context, phantom: PhantomData }),
-            Err(err) => Err((Context { context: self.context, phantom: PhantomData }, err)),
+            Ok(()) => Ok(self.with_state()),
+            Err(err) => Err((self, err)),
         }
     }
 
@@ -64,7 +74,7 @@ impl<T: ContextCurrentState> Context<T> {
     /// [`ContextWrapper::treat_as_not_current`]:
     /// struct.ContextWrapper.html#method.treat_as_not_current
     pub unsafe fn treat_as_not_current(self) -> Context<NotCurrent> {
-        Context { context: self.context, phantom: PhantomData }
+        self.with_state()
     }
 
     /// See [`ContextWrapper::treat_as_current`].
@@ -72,7 +82,16 @@ impl<T: ContextCurrentState> Context<T> {
     /// [`ContextWrapper::treat_as_current`]:
     /// struct.ContextWrapper.html#method.treat_as_current
     pub unsafe fn treat_as_current(self) -> Context<PossiblyCurrent> {
-        Context { context: self.context, phantom: PhantomData }
+        self.with_state()
+    }
+
+    fn with_state<U: ContextCurrentState>(self) -> Context<U> {
+        Context {
+            context: self.context,
+            gl_extensions: self.gl_extensions,
+            procs: self.procs,
+            phantom: PhantomData,
+        }
     }
 
     /// See [`ContextWrapper::is_current`].
@@ -89,6 +108,61 @@ impl<T: ContextCurrentState> Context<T> {
     pub fn get_api(&self) -> Api {
         self.context.get_api()
     }
+
+    /// See [`ContextWrapper::get_api_version`].
+    ///
+    /// [`ContextWrapper::get_api_version`]: struct.ContextWrapper.html#method.get_api_version
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        self.context.get_api_version()
+    }
+
+    /// Returns the scheduling priority the context got, which may be lower
+    /// than the one asked for with [`ContextBuilder::with_priority`].
+    ///
+    /// `None` if the platform can't tell, i.e. without EGL or without
+    /// `EGL_IMG_context_priority`. Mock contexts return the priority asked
+    /// for.
+    ///
+    /// [`ContextBuilder::with_priority`]: struct.ContextBuilder.html#method.with_priority
+    pub fn priority(&self) -> Option<ContextPriority> {
+        self.context.priority()
+    }
+
+    /// Returns the EGL display of the context, or `None` if it doesn't use
+    /// EGL.
+    ///
+    /// Holding on to it keeps the display initialized after the context is
+    /// dropped, so that building another context on it is cheaper.
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub fn egl_display(&self) -> Option<EglDisplay> {
+        self.context.egl_display()
+    }
+
+    /// Returns the extensions of the platform API behind the context, i.e.
+    /// the EGL display extensions, or the GLX or WGL ones.
+    ///
+    /// Always empty on macOS, iOS, with WebGL and with OSMesa.
+    pub fn platform_extensions(&self) -> &[String] {
+        self.context.platform_extensions()
+    }
+
+    /// Returns whether the platform API behind the context supports the
+    /// given extension, e.g. `"EGL_KHR_partial_update"`.
+    ///
+    /// See [`platform_extensions`].
+    ///
+    /// [`platform_extensions`]: #method.platform_extensions
+    pub fn has_platform_extension(&self, extension: &str) -> bool {
+        self.platform_extensions().iter().any(|ext| ext == extension)
+    }
 }
 
 impl Context<PossiblyCurrent> {
@@ -99,6 +173,119 @@ impl Context<PossiblyCurrent> {
     pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
         self.context.get_proc_address(addr)
     }
+
+    /// Returns a [`ProcLoader`] caching the function lookups of the context.
+    ///
+    /// The lookups are the same as with [`get_proc_address`], but only done
+    /// once per function.
+    ///
+    /// [`ProcLoader`]: struct.ProcLoader.html
+    /// [`get_proc_address`]: #method.get_proc_address
+    pub fn proc_loader(&self) -> ProcLoader<'_> {
+        ProcLoader::new(self)
+    }
+
+    /// Returns whether the context was reset, e.g. by a GPU fault, and why,
+    /// with `glGetGraphicsResetStatus`.
+    ///
+    /// Only robust contexts, see [`Robustness`], report resets. Fails with
+    /// [`ContextError::FunctionUnavailable`] if the context supports neither
+    /// OpenGL 4.5, OpenGL ES 3.2 nor `GL_KHR_robustness`,
+    /// `GL_ARB_robustness` or `GL_EXT_robustness`.
+    ///
+    /// [`Robustness`]: enum.Robustness.html
+    /// [`ContextError::FunctionUnavailable`]: enum.ContextError.html#variant.FunctionUnavailable
+    pub fn reset_status(&self) -> Result<ResetStatus, ContextError> {
+        // GLX and glvnd return stubs for any function name, so the function
+        // is only looked up if the context supports it
+        let (es, version) = crate::renderer::gl_version(|addr| self.get_proc_address(addr));
+        let core = if es { version >= (3, 2) } else { version >= (4, 5) };
+        let khr = self.has_gl_extension("GL_KHR_robustness");
+        let name = if core || (khr && !es) {
+            "glGetGraphicsResetStatus"
+        } else if khr {
+            // OpenGL ES suffixes the functions of KHR extensions
+            "glGetGraphicsResetStatusKHR"
+        } else if self.has_gl_extension("GL_ARB_robustness") {
+            "glGetGraphicsResetStatusARB"
+        } else if self.has_gl_extension("GL_EXT_robustness") {
+            "glGetGraphicsResetStatusEXT"
+        } else {
+            return Err(ContextError::FunctionUnavailable);
+        };
+
+        let get_status = unsafe { self.proc_loader().get_fn::<extern "system" fn() -> u32>(name) }
+            .ok_or(ContextError::FunctionUnavailable)?;
+        Ok(ResetStatus::from_gl(get_status()))
+    }
+
+    /// Returns whether the context supports the given OpenGL (ES) extension,
+    /// e.g. `"GL_KHR_debug"`.
+    ///
+    /// The extensions are queried once, with `glGetStringi` from OpenGL (ES)
+    /// 3.0 on and `glGetString` before, then cached.
+    pub fn has_gl_extension(&self, extension: &str) -> bool {
+        let mut gl_extensions = self.gl_extensions.lock().unwrap();
+        gl_extensions
+            .get_or_insert_with(|| {
+                crate::renderer::gl_extensions(|addr| self.get_proc_address(addr))
+            })
+            .contains(extension)
+    }
+
+    /// Returns the renderer and driver behind the context.
+    ///
+    /// The PCI ids and the amount of video memory come from
+    /// `GLX_MESA_query_renderer` with GLX, and the PCI ids from
+    /// `EGL_EXT_device_query` with EGL, on Linux. Everything else comes from
+    /// the GL strings of the context.
+    pub fn renderer_info(&self) -> RendererInfo {
+        self.context.renderer_info()
+    }
+
+    /// Resize the context.
+    ///
+    /// For pbuffer-backed contexts, the pbuffer is recreated with the new
+    /// size. For OSMesa contexts, the buffer rendered into is reallocated.
+    /// Either way the context, and every object created with it, is kept, but
+    /// the content of the default framebuffer is lost.
+    ///
+    /// This does nothing for other headless contexts, e.g. surfaceless ones.
+    ///
+    /// If the new buffer can't be created, e.g. because the size is empty, the
+    /// previous one is kept. Use [`try_resize`] to know about it.
+    ///
+    /// [`try_resize`]: struct.Context.html#method.try_resize
+    pub fn resize(&self, size: dpi::PhysicalSize<u32>) {
+        let _ = self.try_resize(size);
+    }
+
+    /// Like [`resize`], but returns an error when the new buffer can't be
+    /// created or made current. The previous buffer, and its size, are kept
+    /// then.
+    ///
+    /// [`resize`]: struct.Context.html#method.resize
+    pub fn try_resize(&self, size: dpi::PhysicalSize<u32>) -> Result<(), ContextError> {
+        let (width, height) = size.into();
+        self.context.resize(width, height)
+    }
+}
+
+/// Returns the EGL client extensions, which are supported without any display,
+/// e.g. `EGL_EXT_platform_base`.
+///
+/// Empty if libEGL couldn't be loaded, or doesn't support client extensions.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "linux",
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+pub fn egl_client_extensions() -> Vec<String> {
+    crate::api::egl::get_client_extensions()
 }
 
 impl<'a, T: ContextCurrentState> ContextBuilder<'a, T> {
@@ -144,15 +331,150 @@ impl<'a, T: ContextCurrentState> ContextBuilder<'a, T> {
     [`build_osmesa`]: platform/unix/trait.HeadlessContextExt.html#tymethod.build_osmesa\
     "
     )]
+    #[cfg(feature = "winit")]
     pub fn build_headless<TE>(
         self,
         el: &EventLoopWindowTarget<TE>,
         size: dpi::PhysicalSize<u32>,
     ) -> Result<Context<NotCurrent>, CreationError> {
-        let ContextBuilder { pf_reqs, gl_attr } = self;
-        let gl_attr = gl_attr.map_sharing(|ctx| &ctx.context);
-        platform_impl::Context::new_headless(el, &pf_reqs, &gl_attr, size)
-            .map(|context| Context { context, phantom: PhantomData })
+        self.build_with(|pf_reqs, gl_attr| {
+            platform_impl::Context::new_headless(el, pf_reqs, gl_attr, size).map(|ctx| ((), ctx))
+        })
+        .map(|((), context)| context)
+    }
+
+    /// Resolves the context to share with, either from the builder or from its
+    /// [`ShareGroup`], then creates the platform context with `f`.
+    ///
+    /// The group's lock is held for the whole creation so that two contexts
+    /// joining an empty group at once can't both become its anchor.
+    pub(crate) fn build_with<W, F>(self, f: F) -> Result<(W, Context<NotCurrent>), CreationError>
+    where
+        F: FnOnce(
+            &PixelFormatRequirements,
+            &GlAttributes<&platform_impl::Context>,
+        ) -> Result<(W, platform_impl::Context), CreationError>,
+    {
+        let ContextBuilder { pf_reqs, gl_attr, share_group } = self;
+        let mut anchor = share_group.as_ref().map(|group| group.anchor.lock().unwrap());
+        let shared = anchor.as_ref().and_then(|anchor| (**anchor).clone());
+
+        let gl_attr = match shared {
+            Some(_) if gl_attr.sharing.is_some() => {
+                return Err(CreationError::IncompatibleShareContext(
+                    "Cannot share with both a context and a non-empty share group".to_string(),
+                ));
+            }
+            Some(ref shared) => gl_attr.set_sharing(Some(&**shared)),
+            None => gl_attr.map_sharing(|ctx| &*ctx.context),
+        };
+
+        let (w, context) = f(&pf_reqs, &gl_attr)?;
+        let context = Arc::new(context);
+        if let Some(anchor) = anchor.as_mut() {
+            if anchor.is_none() {
+                **anchor = Some(Arc::clone(&context));
+            }
+        }
+
+        Ok((
+            w,
+            Context {
+                context,
+                gl_extensions: Mutex::new(None),
+                procs: Mutex::new(HashMap::new()),
+                phantom: PhantomData,
+            },
+        ))
+    }
+
+    /// Resolves the context to share with like [`build_with`], then runs `f`
+    /// without creating any context, e.g. to find out which config building
+    /// would pick.
+    ///
+    /// [`build_with`]: #method.build_with
+    #[allow(dead_code)]
+    pub(crate) fn query_with<R, F>(&self, f: F) -> Result<R, CreationError>
+    where
+        F: FnOnce(
+            &PixelFormatRequirements,
+            &GlAttributes<&platform_impl::Context>,
+        ) -> Result<R, CreationError>,
+    {
+        let shared =
+            self.share_group.as_ref().and_then(|group| group.anchor.lock().unwrap().clone());
+
+        let gl_attr = match shared {
+            Some(_) if self.gl_attr.sharing.is_some() => {
+                return Err(CreationError::IncompatibleShareContext(
+                    "Cannot share with both a context and a non-empty share group".to_string(),
+                ));
+            }
+            Some(ref shared) => self.gl_attr.clone().set_sharing(Some(&**shared)),
+            None => self.gl_attr.clone().map_sharing(|ctx| &*ctx.context),
+        };
+
+        f(&self.pf_reqs, &gl_attr)
+    }
+}
+
+/// A handle to a set of [`Context`]s which share their OpenGL objects.
+///
+/// Unlike [`ContextBuilder::with_shared_lists`], a `ShareGroup` doesn't borrow
+/// any context: it can be cloned, sent to another thread, and used to build
+/// shared contexts at any later point with
+/// [`ContextBuilder::with_share_group`].
+///
+/// The first context built with the group becomes its anchor. The group keeps
+/// the anchor's native context alive, even after the [`Context`] itself is
+/// dropped, until the last handle to the group goes away. As such, the anchor
+/// must not be current on any thread when that happens, and it is preferable
+/// for it to be a headless context, as a windowed one would outlive its
+/// window.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(not(feature = "winit"))]
+/// # fn main() {}
+/// # #[cfg(feature = "winit")]
+/// # fn main() {
+/// # let el = glutin::event_loop::EventLoop::new();
+/// let group = glutin::ShareGroup::new();
+/// let headless = glutin::ContextBuilder::new()
+///     .with_share_group(&group)
+///     .build_headless(&el, glutin::dpi::PhysicalSize::new(1, 1))
+///     .unwrap();
+///
+/// let group2 = group.clone();
+/// std::thread::spawn(move || {
+///     // Shares its objects with `headless`, even if it was dropped.
+///     let cb = glutin::ContextBuilder::new().with_share_group(&group2);
+/// });
+/// # }
+/// ```
+///
+/// [`Context`]: struct.Context.html
+/// [`ContextBuilder::with_shared_lists`]:
+/// struct.ContextBuilder.html#method.with_shared_lists
+/// [`ContextBuilder::with_share_group`]:
+/// struct.ContextBuilder.html#method.with_share_group
+#[derive(Debug, Clone, Default)]
+pub struct ShareGroup {
+    anchor: Arc<Mutex<Option<Arc<platform_impl::Context>>>>,
+}
+
+impl ShareGroup {
+    /// Creates a new, empty, `ShareGroup`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns true if no [`Context`] has joined this group yet.
+    ///
+    /// [`Context`]: struct.Context.html
+    pub fn is_empty(&self) -> bool {
+        self.anchor.lock().unwrap().is_none()
     }
 }
 
@@ -202,3 +524,4 @@ where
 {
 }
 impl FailToCompileIfNotSendSync for Context<NotCurrent> {}
+impl FailToCompileIfNotSendSync for ShareGroup {}
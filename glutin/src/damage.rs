@@ -0,0 +1,425 @@
+use super::*;
+
+use std::collections::VecDeque;
+
+/// A rectangle in physical pixels.
+///
+/// When submitted as buffer damage, `x` and `y` are the bottom-left corner of
+/// the rectangle, counted from the bottom-left corner of the surface. Winit
+/// counts from the top-left corner instead; [`DamageTracker`] converts between
+/// the two.
+///
+/// [`DamageTracker`]: struct.DamageTracker.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// Returns whether this rectangle covers no pixel.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    ///
+    /// Empty rectangles are ignored.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect { x, y, width: right - x, height: bottom - y }
+    }
+
+    /// Returns the area covered by both rectangles, if any.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if right <= x || bottom <= y {
+            return None;
+        }
+        Some(Rect { x, y, width: right - x, height: bottom - y })
+    }
+
+    /// Returns whether `other` lies entirely within this rectangle.
+    ///
+    /// Empty rectangles are contained in any rectangle.
+    pub fn contains(&self, other: &Rect) -> bool {
+        other.is_empty()
+            || (self.x <= other.x
+                && self.y <= other.y
+                && other.right() <= self.right()
+                && other.bottom() <= self.bottom())
+    }
+
+    /// Returns whether the rectangles overlap or share part of an edge.
+    /// Sharing only a corner doesn't count, as their union would cover more
+    /// than both.
+    fn touches(&self, other: &Rect) -> bool {
+        let overlap_x = self.x < other.right() && other.x < self.right();
+        let overlap_y = self.y < other.bottom() && other.y < self.bottom();
+        let touch_x = self.x <= other.right() && other.x <= self.right();
+        let touch_y = self.y <= other.bottom() && other.y <= self.bottom();
+        (overlap_x && touch_y) || (touch_x && overlap_y)
+    }
+
+    fn right(&self) -> u32 {
+        self.x.saturating_add(self.width)
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y.saturating_add(self.height)
+    }
+}
+
+/// Accumulates the damage of a frame, in winit's physical coordinates, whose
+/// origin is the top-left corner of the surface.
+///
+/// Rectangles are clipped to the surface, and overlapping ones, or ones
+/// sharing part of an edge, are merged into their union.
+///
+/// ```no_run
+/// # #[cfg(feature = "winit")]
+/// # fn example(windowed_context: glutin::WindowedContext<glutin::PossiblyCurrent>) {
+/// use glutin::{DamageTracker, Rect};
+///
+/// let mut damage = DamageTracker::new(windowed_context.window().inner_size());
+/// damage.add(Rect { x: 10, y: 10, width: 100, height: 20 });
+/// windowed_context.swap_buffers_with_damage(&damage.swap_rects()).unwrap();
+/// damage.clear();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DamageTracker {
+    size: dpi::PhysicalSize<u32>,
+    rects: Vec<Rect>,
+}
+
+impl DamageTracker {
+    /// Creates an empty tracker for a surface of the given size.
+    pub fn new(size: dpi::PhysicalSize<u32>) -> Self {
+        DamageTracker { size, rects: Vec::new() }
+    }
+
+    /// Changes the size of the surface, clipping the damage to it.
+    pub fn resize(&mut self, size: dpi::PhysicalSize<u32>) {
+        self.size = size;
+        let bounds = self.bounds();
+        self.rects = self.rects.iter().filter_map(|rect| rect.intersection(&bounds)).collect();
+    }
+
+    /// Adds a rectangle, with its origin at the top-left corner of the
+    /// surface.
+    pub fn add(&mut self, rect: Rect) {
+        let mut rect = match rect.intersection(&self.bounds()) {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        // merging may make the rectangle touch ones it didn't before
+        while let Some(index) = self.rects.iter().position(|other| other.touches(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(index));
+        }
+        self.rects.push(rect);
+    }
+
+    /// Damages the whole surface.
+    pub fn add_all(&mut self) {
+        self.rects.clear();
+        self.rects.push(self.bounds());
+    }
+
+    /// Returns the damage, with its origin at the top-left corner of the
+    /// surface.
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Returns the damage converted to the bottom-left origin expected by
+    /// [`swap_buffers_with_damage`] and [`set_damage_region`].
+    ///
+    /// [`swap_buffers_with_damage`]: struct.ContextWrapper.html#method.swap_buffers_with_damage
+    /// [`set_damage_region`]: struct.ContextWrapper.html#method.set_damage_region
+    pub fn swap_rects(&self) -> Vec<Rect> {
+        self.rects
+            .iter()
+            .map(|rect| Rect { y: self.size.height - rect.bottom(), ..*rect })
+            .collect()
+    }
+
+    /// Returns whether nothing was damaged.
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Forgets the damage, e.g. once the frame was presented.
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect { x: 0, y: 0, width: self.size.width, height: self.size.height }
+    }
+}
+
+/// Remembers the damage of the last frames, to work out what has to be
+/// repainted in a back buffer of a given [`buffer_age`].
+///
+/// A back buffer of age `n` holds the frame presented `n` swaps ago, so it
+/// misses the damage of the `n - 1` frames presented since, on top of the
+/// damage of the frame being drawn.
+///
+/// ```no_run
+/// # #[cfg(feature = "winit")]
+/// # fn example(windowed_context: glutin::WindowedContext<glutin::PossiblyCurrent>) {
+/// use glutin::{DamageHistory, Rect};
+///
+/// let mut history = DamageHistory::new(3);
+/// # let frame_damage = vec![Rect { x: 0, y: 0, width: 100, height: 100 }];
+/// let repaint = windowed_context.buffer_age().and_then(|age| history.damage_since(age));
+/// match repaint {
+///     // repaint `frame_damage` and `rects`
+///     Some(rects) => (),
+///     // repaint everything
+///     None => (),
+/// }
+/// history.push(&frame_damage);
+/// windowed_context.swap_buffers_with_damage(&frame_damage).unwrap();
+/// # }
+/// ```
+///
+/// [`buffer_age`]: struct.ContextWrapper.html#method.buffer_age
+#[derive(Debug, Clone)]
+pub struct DamageHistory {
+    frames: VecDeque<Vec<Rect>>,
+    capacity: usize,
+}
+
+impl DamageHistory {
+    /// Creates a history remembering the damage of up to `capacity` frames,
+    /// enough for back buffers up to `capacity + 1` frames old.
+    pub fn new(capacity: usize) -> Self {
+        DamageHistory { frames: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Records the damage of the frame about to be presented.
+    pub fn push(&mut self, damage: &[Rect]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_back();
+        }
+        self.frames.push_front(damage.to_vec());
+    }
+
+    /// Returns the damage of the frames presented since a back buffer of the
+    /// given age was last drawn to.
+    ///
+    /// Returns `None` when its content is unknown, that is when `age` is `0`
+    /// or older than the history, in which case everything has to be
+    /// repainted.
+    pub fn damage_since(&self, age: u32) -> Option<Vec<Rect>> {
+        let frames = (age as usize).checked_sub(1)?;
+        if frames > self.frames.len() {
+            return None;
+        }
+        Some(self.frames.iter().take(frames).flatten().copied().collect())
+    }
+
+    /// Forgets every frame, e.g. after a resize, when back buffers lose their
+    /// content.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: u32) -> Rect {
+        Rect { x, y: 0, width: 1, height: 1 }
+    }
+
+    fn tracker() -> DamageTracker {
+        DamageTracker::new(dpi::PhysicalSize::new(100, 100))
+    }
+
+    #[test]
+    fn tracker_merges_overlapping() {
+        let mut damage = tracker();
+        damage.add(Rect { x: 0, y: 0, width: 20, height: 20 });
+        damage.add(Rect { x: 10, y: 10, width: 20, height: 20 });
+        assert_eq!(damage.rects(), &[Rect { x: 0, y: 0, width: 30, height: 30 }]);
+    }
+
+    #[test]
+    fn tracker_merges_adjacent() {
+        let mut damage = tracker();
+        damage.add(Rect { x: 0, y: 0, width: 10, height: 10 });
+        damage.add(Rect { x: 10, y: 0, width: 10, height: 10 });
+        damage.add(Rect { x: 0, y: 10, width: 20, height: 10 });
+        assert_eq!(damage.rects(), &[Rect { x: 0, y: 0, width: 20, height: 20 }]);
+    }
+
+    #[test]
+    fn tracker_keeps_corner_touching_apart() {
+        let mut damage = tracker();
+        damage.add(Rect { x: 0, y: 0, width: 10, height: 10 });
+        damage.add(Rect { x: 10, y: 10, width: 10, height: 10 });
+        assert_eq!(damage.rects().len(), 2);
+    }
+
+    #[test]
+    fn tracker_keeps_disjoint_apart() {
+        let mut damage = tracker();
+        damage.add(Rect { x: 0, y: 0, width: 10, height: 10 });
+        damage.add(Rect { x: 50, y: 0, width: 10, height: 10 });
+        assert_eq!(
+            damage.rects(),
+            &[
+                Rect { x: 0, y: 0, width: 10, height: 10 },
+                Rect { x: 50, y: 0, width: 10, height: 10 }
+            ]
+        );
+    }
+
+    #[test]
+    fn rect_union() {
+        let a = Rect { x: 0, y: 0, width: 10, height: 10 };
+        let b = Rect { x: 20, y: 5, width: 10, height: 20 };
+        assert_eq!(a.union(&b), Rect { x: 0, y: 0, width: 30, height: 25 });
+        assert_eq!(a.union(&Rect { x: 50, y: 50, width: 0, height: 10 }), a);
+        assert_eq!(Rect { x: 50, y: 50, width: 10, height: 0 }.union(&b), b);
+    }
+
+    #[test]
+    fn rect_intersection() {
+        let a = Rect { x: 0, y: 0, width: 10, height: 10 };
+        let b = Rect { x: 5, y: 5, width: 10, height: 10 };
+        assert_eq!(a.intersection(&b), Some(Rect { x: 5, y: 5, width: 5, height: 5 }));
+        // sharing an edge covers no pixel
+        assert_eq!(a.intersection(&Rect { x: 10, y: 0, width: 10, height: 10 }), None);
+        assert_eq!(a.intersection(&Rect { x: 20, y: 20, width: 10, height: 10 }), None);
+    }
+
+    #[test]
+    fn rect_contains() {
+        let a = Rect { x: 0, y: 0, width: 10, height: 10 };
+        assert!(a.contains(&a));
+        assert!(a.contains(&Rect { x: 2, y: 2, width: 8, height: 8 }));
+        assert!(!a.contains(&Rect { x: 2, y: 2, width: 9, height: 8 }));
+        assert!(a.contains(&Rect { x: 50, y: 50, width: 0, height: 0 }));
+    }
+
+    #[test]
+    fn rect_touches() {
+        let a = Rect { x: 10, y: 10, width: 10, height: 10 };
+        // overlapping
+        assert!(a.touches(&Rect { x: 15, y: 15, width: 10, height: 10 }));
+        // sharing part of an edge, on each side
+        assert!(a.touches(&Rect { x: 20, y: 15, width: 10, height: 10 }));
+        assert!(a.touches(&Rect { x: 0, y: 15, width: 10, height: 10 }));
+        assert!(a.touches(&Rect { x: 15, y: 20, width: 10, height: 10 }));
+        assert!(a.touches(&Rect { x: 15, y: 0, width: 10, height: 10 }));
+        // sharing a corner only
+        assert!(!a.touches(&Rect { x: 20, y: 20, width: 10, height: 10 }));
+        assert!(!a.touches(&Rect { x: 0, y: 0, width: 10, height: 10 }));
+        assert!(!a.touches(&Rect { x: 30, y: 10, width: 10, height: 10 }));
+    }
+
+    #[test]
+    fn tracker_clips_to_surface() {
+        let mut damage = tracker();
+        damage.add(Rect { x: 90, y: 80, width: 20, height: 30 });
+        damage.add(Rect { x: 200, y: 0, width: 10, height: 10 });
+        damage.add(Rect { x: 0, y: 0, width: 10, height: 0 });
+        assert_eq!(damage.rects(), &[Rect { x: 90, y: 80, width: 10, height: 20 }]);
+
+        damage.resize(dpi::PhysicalSize::new(95, 90));
+        assert_eq!(damage.rects(), &[Rect { x: 90, y: 80, width: 5, height: 10 }]);
+        damage.resize(dpi::PhysicalSize::new(50, 50));
+        assert!(damage.is_empty());
+
+        damage.add_all();
+        assert_eq!(damage.rects(), &[Rect { x: 0, y: 0, width: 50, height: 50 }]);
+    }
+
+    #[test]
+    fn tracker_flips_swap_rects() {
+        let mut damage = DamageTracker::new(dpi::PhysicalSize::new(100, 80));
+        damage.add(Rect { x: 10, y: 0, width: 20, height: 30 });
+        damage.add(Rect { x: 50, y: 70, width: 10, height: 10 });
+        assert_eq!(
+            damage.swap_rects(),
+            [
+                Rect { x: 10, y: 50, width: 20, height: 30 },
+                Rect { x: 50, y: 0, width: 10, height: 10 }
+            ]
+        );
+
+        // the flip follows the size of the surface
+        damage.resize(dpi::PhysicalSize::new(100, 75));
+        assert_eq!(
+            damage.swap_rects(),
+            [
+                Rect { x: 10, y: 45, width: 20, height: 30 },
+                Rect { x: 50, y: 0, width: 10, height: 5 }
+            ]
+        );
+    }
+
+    #[test]
+    fn history_age_zero() {
+        let mut history = DamageHistory::new(2);
+        history.push(&[rect(0)]);
+        assert_eq!(history.damage_since(0), None);
+    }
+
+    #[test]
+    fn history_ages() {
+        let mut history = DamageHistory::new(2);
+        assert_eq!(history.damage_since(1), Some(vec![]));
+        assert_eq!(history.damage_since(2), None);
+
+        history.push(&[rect(0)]);
+        history.push(&[rect(1)]);
+        assert_eq!(history.damage_since(1), Some(vec![]));
+        assert_eq!(history.damage_since(2), Some(vec![rect(1)]));
+        assert_eq!(history.damage_since(3), Some(vec![rect(1), rect(0)]));
+        // beyond the history
+        assert_eq!(history.damage_since(4), None);
+    }
+
+    #[test]
+    fn history_trims_oldest_frames() {
+        let mut history = DamageHistory::new(2);
+        history.push(&[rect(0)]);
+        history.push(&[rect(1)]);
+        history.push(&[rect(2)]);
+        assert_eq!(history.damage_since(3), Some(vec![rect(2), rect(1)]));
+        assert_eq!(history.damage_since(4), None);
+
+        let mut history = DamageHistory::new(0);
+        history.push(&[rect(0)]);
+        assert_eq!(history.damage_since(1), Some(vec![]));
+        assert_eq!(history.damage_since(2), None);
+
+        history.clear();
+        assert_eq!(history.damage_since(2), None);
+    }
+}
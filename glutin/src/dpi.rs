@@ -0,0 +1,61 @@
+//! The subset of winit's `dpi` module used by glutin, for builds without
+//! winit.
+
+/// A size in physical pixels.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhysicalSize<P> {
+    pub width: P,
+    pub height: P,
+}
+
+impl<P> PhysicalSize<P> {
+    #[inline]
+    pub const fn new(width: P, height: P) -> Self {
+        PhysicalSize { width, height }
+    }
+}
+
+impl<P> From<(P, P)> for PhysicalSize<P> {
+    #[inline]
+    fn from((width, height): (P, P)) -> Self {
+        PhysicalSize::new(width, height)
+    }
+}
+
+impl<P> From<PhysicalSize<P>> for (P, P) {
+    #[inline]
+    fn from(size: PhysicalSize<P>) -> Self {
+        (size.width, size.height)
+    }
+}
+
+/// A position in logical pixels, which the scale factor converts to
+/// physical pixels.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogicalPosition<P> {
+    pub x: P,
+    pub y: P,
+}
+
+impl<P> LogicalPosition<P> {
+    #[inline]
+    pub const fn new(x: P, y: P) -> Self {
+        LogicalPosition { x, y }
+    }
+}
+
+impl<P> From<(P, P)> for LogicalPosition<P> {
+    #[inline]
+    fn from((x, y): (P, P)) -> Self {
+        LogicalPosition::new(x, y)
+    }
+}
+
+impl<P> From<LogicalPosition<P>> for (P, P) {
+    #[inline]
+    fn from(position: LogicalPosition<P>) -> Self {
+        (position.x, position.y)
+    }
+}
@@ -11,6 +11,9 @@
 //! of building a [`WindowedContext<T>`]:
 //!
 //! ```no_run
+//! # #[cfg(not(feature = "winit"))]
+//! # fn main() {}
+//! # #[cfg(feature = "winit")]
 //! # fn main() {
 //! let el = glutin::event_loop::EventLoop::new();
 //! let wb = glutin::window::WindowBuilder::new()
@@ -85,20 +88,67 @@ extern crate lazy_static;
 #[macro_use]
 extern crate objc;
 
+#[cfg(all(
+    not(feature = "winit"),
+    not(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))
+))]
+compile_error!("the `winit` feature is only optional on Linux and the BSDs");
+
 pub mod platform;
 
 mod api;
 mod context;
+mod damage;
 mod platform_impl;
+mod proc_loader;
+mod renderer;
+mod reset;
 mod windowed;
 
+#[cfg(not(feature = "winit"))]
+pub mod dpi;
+#[cfg(feature = "mock")]
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+pub mod mock;
+
+#[cfg(any(
+    target_os = "windows",
+    target_os = "linux",
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+pub use crate::api::egl::EglDisplay;
 pub use crate::context::*;
+pub use crate::damage::*;
+pub use crate::proc_loader::*;
+pub use crate::renderer::*;
+pub use crate::reset::*;
 pub use crate::windowed::*;
+#[cfg(feature = "winit")]
 pub use winit::*;
 
+#[cfg(feature = "winit")]
 use winit::error::OsError;
 
 use std::io;
+use std::ops::RangeInclusive;
 
 /// An object that allows you to build [`Context`]s, [`RawContext<T>`]s and
 /// [`WindowedContext<T>`]s.
@@ -106,15 +156,26 @@ use std::io;
 /// One notable limitation of the Wayland backend when it comes to shared
 /// [`Context`]s is that both contexts must use the same events loop.
 ///
+/// With the `serde` feature, a `ContextBuilder` can be (de)serialized, e.g.
+/// from a configuration file. The context to share with and the share group
+/// are left out.
+///
 /// [`Context`]: struct.Context.html
 /// [`WindowedContext<T>`]: type.WindowedContext.html
 /// [`RawContext<T>`]: type.RawContext.html
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
 pub struct ContextBuilder<'a, T: ContextCurrentState> {
     /// The attributes to use to create the context.
     pub gl_attr: GlAttributes<&'a Context<T>>,
     /// The pixel format requirements
     pub pf_reqs: PixelFormatRequirements,
+    /// The [`ShareGroup`] the context joins, if any. Never (de)serialized.
+    ///
+    /// [`ShareGroup`]: struct.ShareGroup.html
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub share_group: Option<ShareGroup>,
 }
 
 impl<'a> ContextBuilder<'a, NotCurrent> {
@@ -123,6 +184,7 @@ impl<'a> ContextBuilder<'a, NotCurrent> {
         ContextBuilder {
             pf_reqs: std::default::Default::default(),
             gl_attr: std::default::Default::default(),
+            share_group: None,
         }
     }
 }
@@ -168,6 +230,22 @@ impl<'a, T: ContextCurrentState> ContextBuilder<'a, T> {
         self
     }
 
+    /// Asks for the OpenGL [`Context`] to be scheduled with the given
+    /// priority relative to other contexts. See the docs of
+    /// [`ContextPriority`].
+    ///
+    /// This is only a hint, ignored by drivers not supporting it. Use
+    /// [`Context::priority`] to know the priority the context got.
+    ///
+    /// [`Context`]: struct.Context.html
+    /// [`ContextPriority`]: enum.ContextPriority.html
+    /// [`Context::priority`]: struct.Context.html#method.priority
+    #[inline]
+    pub fn with_priority(mut self, priority: ContextPriority) -> Self {
+        self.gl_attr.priority = Some(priority);
+        self
+    }
+
     /// Requests that the window has vsync enabled.
     ///
     /// By default, vsync is not enabled.
@@ -185,7 +263,54 @@ impl<'a, T: ContextCurrentState> ContextBuilder<'a, T> {
         self,
         other: &'a Context<T2>,
     ) -> ContextBuilder<'a, T2> {
-        ContextBuilder { gl_attr: self.gl_attr.set_sharing(Some(other)), pf_reqs: self.pf_reqs }
+        ContextBuilder {
+            gl_attr: self.gl_attr.set_sharing(Some(other)),
+            pf_reqs: self.pf_reqs,
+            share_group: self.share_group,
+        }
+    }
+
+    /// Share the display lists with every other [`Context`] of the given
+    /// [`ShareGroup`].
+    ///
+    /// If the group is still empty, the built [`Context`] becomes its anchor.
+    /// Building fails with [`IncompatibleShareContext`] if the group was
+    /// created by another backend, or if [`with_shared_lists`] was also used
+    /// on a non-empty group.
+    ///
+    /// [`Context`]: struct.Context.html
+    /// [`ShareGroup`]: struct.ShareGroup.html
+    /// [`IncompatibleShareContext`]:
+    /// enum.CreationError.html#variant.IncompatibleShareContext
+    /// [`with_shared_lists`]: struct.ContextBuilder.html#method.with_shared_lists
+    #[inline]
+    pub fn with_share_group(mut self, group: &ShareGroup) -> Self {
+        self.share_group = Some(group.clone());
+        self
+    }
+
+    /// Builds the context on the given EGL display, e.g. one obtained from
+    /// [`Context::egl_display`], instead of the display of the native
+    /// display. This forces EGL on X11, where GLX would be tried first.
+    ///
+    /// Windowed contexts still need the display to be the one of their
+    /// window's native display, so this is mostly useful to build headless
+    /// contexts alongside another context.
+    ///
+    /// [`Context::egl_display`]: struct.Context.html#method.egl_display
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    #[inline]
+    pub fn with_egl_display(mut self, display: &EglDisplay) -> Self {
+        self.pf_reqs.egl_display = Some(display.clone());
+        self
     }
 
     /// Sets the multisampling level to request. A value of `0` indicates that
@@ -290,7 +415,15 @@ pub enum CreationError {
     OpenGlVersionNotSupported,
     NoAvailablePixelFormat,
     PlatformSpecific(String),
+    /// The window could not be created.
+    #[cfg(feature = "winit")]
     Window(OsError),
+    /// The window could not be created.
+    #[cfg(not(feature = "winit"))]
+    Window(WindowError),
+    /// The context to share with cannot share objects with the requested
+    /// context, usually because they belong to different backends.
+    IncompatibleShareContext(String),
     /// We received multiple errors, instead of one.
     CreationErrors(Vec<Box<CreationError>>),
 }
@@ -329,6 +462,7 @@ impl CreationError {
             }
             CreationError::PlatformSpecific(text) => text.clone(),
             CreationError::Window(err) => err.to_string(),
+            CreationError::IncompatibleShareContext(text) => text.clone(),
             CreationError::CreationErrors(_) => "Received multiple errors.".to_string(),
         }
     }
@@ -362,12 +496,43 @@ impl std::error::Error for CreationError {
     }
 }
 
+#[cfg(feature = "winit")]
 impl From<OsError> for CreationError {
     fn from(err: OsError) -> Self {
         CreationError::Window(err)
     }
 }
 
+/// Error of the windowing library, as returned in [`CreationError::Window`]
+/// without the `winit` feature.
+///
+/// [`CreationError::Window`]: enum.CreationError.html#variant.Window
+#[cfg(not(feature = "winit"))]
+#[derive(Debug)]
+pub struct WindowError(Box<dyn std::error::Error + Send + Sync>);
+
+#[cfg(not(feature = "winit"))]
+impl WindowError {
+    /// Returns the error of the windowing library.
+    pub fn get_ref(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        &*self.0
+    }
+}
+
+#[cfg(not(feature = "winit"))]
+impl std::fmt::Display for WindowError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        self.0.fmt(formatter)
+    }
+}
+
+#[cfg(not(feature = "winit"))]
+impl std::error::Error for WindowError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
 /// Error that can happen when manipulating an OpenGL [`Context`].
 ///
 /// [`Context`]: struct.Context.html
@@ -395,6 +560,7 @@ impl std::error::Error for ContextError {}
 
 /// All APIs related to OpenGL that you can possibly get while using glutin.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Api {
     /// The classical OpenGL. Available on Windows, Unix operating systems,
     /// OS/X.
@@ -409,6 +575,7 @@ pub enum Api {
 ///
 /// [`Context`]: struct.Context.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GlProfile {
     /// Include all the immediate more functions and definitions.
     Compatibility,
@@ -418,7 +585,8 @@ pub enum GlProfile {
 
 /// Describes the OpenGL API and version that are being requested when a context
 /// is created.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GlRequest {
     /// Request the latest version of the "best" API of this platform.
     ///
@@ -441,6 +609,24 @@ pub enum GlRequest {
         /// The version to use for OpenGL ES.
         opengles_version: (u8, u8),
     },
+
+    /// Try each API in turn, with any of its versions within the given range,
+    /// from the highest to the lowest.
+    ///
+    /// Example: any OpenGL version from 3.3 to 4.6, else OpenGL ES 3.0 to 3.2:
+    ///
+    /// ```
+    /// # use glutin::{Api, GlRequest};
+    /// GlRequest::Ranges(vec![(Api::OpenGl, (3, 3)..=(4, 6)), (Api::OpenGlEs, (3, 0)..=(3, 2))]);
+    /// ```
+    ///
+    /// When none of the versions of an API can be created, the next entry is
+    /// tried. On X11, GLX only tries the OpenGL entries, and EGL is tried with
+    /// all of them if GLX fails.
+    ///
+    /// This is honoured by the EGL, GLX and OSMesa backends. Other backends
+    /// only request the highest version of the first entry.
+    Ranges(Vec<(Api, RangeInclusive<(u8, u8)>)>),
 }
 
 impl GlRequest {
@@ -449,15 +635,69 @@ impl GlRequest {
         match self {
             &GlRequest::Specific(Api::OpenGl, opengl_version) => Some(opengl_version),
             &GlRequest::GlThenGles { opengl_version, .. } => Some(opengl_version),
+            GlRequest::Ranges(ranges) => ranges
+                .iter()
+                .filter(|(api, _)| *api == Api::OpenGl)
+                .find_map(|(api, range)| api.versions_in(range).first().copied()),
             _ => None,
         }
     }
+
+    /// Turns `Ranges` into a `Specific` request for the highest version of its
+    /// first entry, for backends which can't retry context creation.
+    #[allow(dead_code)]
+    pub(crate) fn highest_specific(&self) -> GlRequest {
+        match self {
+            GlRequest::Ranges(ranges) => ranges
+                .iter()
+                .find_map(|(api, range)| {
+                    api.versions_in(range)
+                        .first()
+                        .map(|&version| GlRequest::Specific(*api, version))
+                })
+                .unwrap_or(GlRequest::Latest),
+            request => request.clone(),
+        }
+    }
+}
+
+impl Api {
+    /// Returns the released versions of this API within `range`, from the
+    /// highest to the lowest.
+    pub(crate) fn versions_in(self, range: &RangeInclusive<(u8, u8)>) -> Vec<(u8, u8)> {
+        let versions: &[(u8, u8)] = match self {
+            Api::OpenGl => &[
+                (4, 6),
+                (4, 5),
+                (4, 4),
+                (4, 3),
+                (4, 2),
+                (4, 1),
+                (4, 0),
+                (3, 3),
+                (3, 2),
+                (3, 1),
+                (3, 0),
+                (2, 1),
+                (2, 0),
+                (1, 5),
+                (1, 4),
+                (1, 3),
+                (1, 2),
+                (1, 1),
+                (1, 0),
+            ],
+            Api::OpenGlEs => &[(3, 2), (3, 1), (3, 0), (2, 0), (1, 1), (1, 0)],
+            Api::WebGl => &[(2, 0), (1, 0)],
+        };
+        versions.iter().copied().filter(|version| range.contains(version)).collect()
+    }
 }
 
 /// The minimum core profile GL context. Useful for getting the minimum
 /// required GL version while still running on OSX, which often forbids
 /// the compatibility profile features.
-pub static GL_CORE: GlRequest = GlRequest::Specific(Api::OpenGl, (3, 2));
+pub const GL_CORE: GlRequest = GlRequest::Specific(Api::OpenGl, (3, 2));
 
 /// Specifies the tolerance of the OpenGL [`Context`] to faults. If you accept
 /// raw OpenGL commands and/or raw shader code from an untrusted source, you
@@ -465,6 +705,7 @@ pub static GL_CORE: GlRequest = GlRequest::Specific(Api::OpenGl, (3, 2));
 ///
 /// [`Context`]: struct.Context.html
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Robustness {
     /// Not everything is checked. Your application can crash if you do
     /// something wrong with your shaders.
@@ -507,10 +748,31 @@ pub enum Robustness {
     TryRobustLoseContextOnReset,
 }
 
+/// How the GPU should schedule the work of a [`Context`] relative to other
+/// contexts, e.g. to let a compositor preempt background work.
+///
+/// Only supported with EGL, through `EGL_IMG_context_priority`. Drivers may
+/// give a lower priority than requested, e.g. when the process lacks the
+/// rights for `High`.
+///
+/// [`Context`]: struct.Context.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContextPriority {
+    High,
+    /// The priority contexts get by default.
+    Medium,
+    Low,
+}
+
 /// The behavior of the driver when you change the current context.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReleaseBehavior {
     /// Doesn't do anything. Most notably doesn't flush.
+    ///
+    /// With EGL, building the context fails if
+    /// `EGL_KHR_context_flush_control` isn't supported.
     None,
 
     /// Flushes the context that was previously current as if `glFlush` was
@@ -521,6 +783,7 @@ pub enum ReleaseBehavior {
 /// Describes a possible format.
 #[allow(missing_docs)]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PixelFormat {
     pub hardware_accelerated: bool,
     /// The number of color bits. Does not include alpha bits.
@@ -534,11 +797,15 @@ pub struct PixelFormat {
     /// the multisampling level.
     pub multisampling: Option<u16>,
     pub srgb: bool,
+    /// Whether the windowing system blends the window with what's behind it
+    /// using the alpha channel. Always `false` for headless contexts.
+    pub transparency: bool,
 }
 
 /// Describes how the backend should choose a pixel format.
 // TODO: swap method? (swap, copy)
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PixelFormatRequirements {
     /// If true, only hardware-accelerated formats will be considered. If
     /// false, only software renderers. `None` means "don't care". Default
@@ -589,9 +856,39 @@ pub struct PixelFormatRequirements {
     /// The behavior when changing the current context. Default is `Flush`.
     pub release_behavior: ReleaseBehavior,
 
-    /// X11 only: set internally to insure a certain visual xid is used when
-    /// choosing the fbconfig.
-    pub(crate) x11_visual_xid: Option<std::os::raw::c_ulong>,
+    /// X11 only: the screen and visual to create the context with, set with
+    /// `ContextBuilderExtUnix`. Default is no requirement.
+    ///
+    /// Never (de)serialized: visual ids are meaningless outside of the
+    /// running X server, and screens outside of the running display.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) x11: platform_impl::X11Requirements,
+
+    /// EGL only: the display to create the context on, set with
+    /// [`ContextBuilder::with_egl_display`]. Default is `None`, which uses
+    /// the display of the native display.
+    ///
+    /// Never (de)serialized.
+    ///
+    /// [`ContextBuilder::with_egl_display`]: struct.ContextBuilder.html#method.with_egl_display
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub egl_display: Option<EglDisplay>,
 }
 
 impl Default for PixelFormatRequirements {
@@ -609,7 +906,24 @@ impl Default for PixelFormatRequirements {
             stereoscopy: false,
             srgb: true,
             release_behavior: ReleaseBehavior::Flush,
-            x11_visual_xid: None,
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+            ))]
+            x11: Default::default(),
+            #[cfg(any(
+                target_os = "windows",
+                target_os = "linux",
+                target_os = "android",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+            ))]
+            egl_display: None,
         }
     }
 }
@@ -618,10 +932,13 @@ impl Default for PixelFormatRequirements {
 ///
 /// [`Context`]: struct.Context.html
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
 pub struct GlAttributes<S> {
     /// An existing context with which some OpenGL objects get shared.
     ///
-    /// The default is `None`.
+    /// The default is `None`. Never (de)serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub sharing: Option<S>,
 
     /// Version to try create. See [`GlRequest`] for more infos.
@@ -655,6 +972,13 @@ pub struct GlAttributes<S> {
     /// enum.Robustness.html#variant.TryRobustLoseContextOnReset
     pub robustness: Robustness,
 
+    /// The scheduling priority to ask for. See [`ContextPriority`].
+    ///
+    /// The default is `None`, which leaves it to the driver.
+    ///
+    /// [`ContextPriority`]: enum.ContextPriority.html
+    pub priority: Option<ContextPriority>,
+
     /// Whether to use vsync. If vsync is enabled, calling `swap_buffers` will
     /// block until the screen refreshes. This is typically used to prevent
     /// screen tearing.
@@ -676,6 +1000,7 @@ impl<S> GlAttributes<S> {
             profile: self.profile,
             debug: self.debug,
             robustness: self.robustness,
+            priority: self.priority,
             vsync: self.vsync,
         }
     }
@@ -689,6 +1014,7 @@ impl<S> GlAttributes<S> {
             profile: self.profile,
             debug: self.debug,
             robustness: self.robustness,
+            priority: self.priority,
             vsync: self.vsync,
         }
     }
@@ -703,16 +1029,37 @@ impl<S> Default for GlAttributes<S> {
             profile: None,
             debug: cfg!(debug_assertions),
             robustness: Robustness::NotRobust,
+            priority: None,
             vsync: false,
         }
     }
 }
 
-// Rectangles to submit as buffer damage.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Rect {
-    pub x: u32,
-    pub y: u32,
-    pub width: u32,
-    pub height: u32,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_in_range() {
+        assert_eq!(
+            Api::OpenGl.versions_in(&((3, 2)..=(4, 1))),
+            vec![(4, 1), (4, 0), (3, 3), (3, 2)]
+        );
+        assert_eq!(Api::OpenGlEs.versions_in(&((2, 0)..=(3, 0))), vec![(3, 0), (2, 0)]);
+        assert_eq!(Api::WebGl.versions_in(&((1, 0)..=(2, 0))), vec![(2, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn versions_in_unreleased_bounds() {
+        // bounds needn't be released versions themselves
+        assert_eq!(Api::OpenGl.versions_in(&((3, 4)..=(4, 0))), vec![(4, 0)]);
+        assert_eq!(Api::OpenGlEs.versions_in(&((2, 5)..=(9, 9))), vec![(3, 2), (3, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn versions_in_empty_range() {
+        assert!(Api::OpenGl.versions_in(&((4, 6)..=(3, 3))).is_empty());
+        assert!(Api::OpenGlEs.versions_in(&((4, 0)..=(4, 6))).is_empty());
+        assert_eq!(Api::OpenGl.versions_in(&((3, 3)..=(3, 3))), vec![(3, 3)]);
+    }
 }
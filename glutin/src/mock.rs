@@ -0,0 +1,310 @@
+//! A backend implemented entirely in Rust, to unit test the code around
+//! context creation, swapping, resizing and context loss without any GL
+//! stack. Requires the `mock` feature, on Windows, macOS, Linux and the BSDs.
+//!
+//! Contexts built with [`ContextBuilder::build_mock`] render nothing and
+//! return null for every function. They record the calls made on them into
+//! their [`MockBackend`], which can also make these calls fail.
+//!
+//! # Example
+//!
+//! ```
+//! use glutin::dpi::PhysicalSize;
+//! use glutin::mock::{MockBackend, MockCall, MockFailure, MockOp};
+//! use glutin::ContextError;
+//!
+//! let backend = MockBackend::new();
+//! let context = glutin::ContextBuilder::new()
+//!     .build_mock(&backend, PhysicalSize::new(800, 600))
+//!     .unwrap();
+//! let context = unsafe { context.make_current().unwrap() };
+//!
+//! backend.fail_next(MockOp::SwapBuffers, MockFailure::ContextLost);
+//! assert!(matches!(context.swap_buffers(), Err(ContextError::ContextLost)));
+//! assert_eq!(backend.calls(), [MockCall::Create, MockCall::MakeCurrent, MockCall::SwapBuffers]);
+//! ```
+//!
+//! [`ContextBuilder::build_mock`]: ../struct.ContextBuilder.html#method.build_mock
+//! [`MockBackend`]: struct.MockBackend.html
+
+use crate::api::mock::MockContext;
+use crate::{
+    dpi, platform_impl, ContextBuilder, ContextCurrentState, ContextError, CreationError,
+    NotCurrent, RawContext, Rect,
+};
+
+use std::sync::{Arc, Mutex};
+
+/// Records the calls made on the contexts built with it, and fails the ones
+/// it's told to.
+///
+/// Clones share the same record.
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    calls: Vec<MockCall>,
+    failures: Vec<(MockOp, MockFailure)>,
+    next_id: u64,
+    // the contexts with a lower id are lost
+    lost_below: u64,
+}
+
+/// A call made on a mock context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    /// The context was built.
+    Create,
+    MakeCurrent,
+    MakeNotCurrent,
+    SwapBuffers,
+    SwapBuffersWithDamage(Vec<Rect>),
+    Resize {
+        width: u32,
+        height: u32,
+    },
+    /// The context was dropped.
+    Destroy,
+}
+
+/// The calls [`MockBackend::fail_next`] can fail.
+///
+/// [`MockBackend::fail_next`]: struct.MockBackend.html#method.fail_next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockOp {
+    /// Building a context.
+    Create,
+    MakeCurrent,
+    MakeNotCurrent,
+    /// Swapping buffers, with or without damage.
+    SwapBuffers,
+    /// Resizing a context, see [`Context::try_resize`].
+    ///
+    /// [`Context::try_resize`]: ../struct.Context.html#method.try_resize
+    Resize,
+}
+
+/// An error [`MockBackend::fail_next`] can inject.
+///
+/// [`MockBackend::fail_next`]: struct.MockBackend.html#method.fail_next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockFailure {
+    /// [`ContextError::ContextLost`]. Fails the creation with an
+    /// [`CreationError::OsError`].
+    ///
+    /// [`ContextError::ContextLost`]: ../enum.ContextError.html#variant.ContextLost
+    /// [`CreationError::OsError`]: ../enum.CreationError.html#variant.OsError
+    ContextLost,
+    /// [`CreationError::NoAvailablePixelFormat`]. Fails other calls with a
+    /// [`ContextError::OsError`].
+    ///
+    /// [`CreationError::NoAvailablePixelFormat`]: ../enum.CreationError.html#variant.NoAvailablePixelFormat
+    /// [`ContextError::OsError`]: ../enum.ContextError.html#variant.OsError
+    NoAvailablePixelFormat,
+    /// An `OsError` with the given message.
+    OsError(String),
+}
+
+impl MockFailure {
+    fn into_creation_error(self) -> CreationError {
+        match self {
+            MockFailure::ContextLost => CreationError::OsError("context lost".to_string()),
+            MockFailure::NoAvailablePixelFormat => CreationError::NoAvailablePixelFormat,
+            MockFailure::OsError(msg) => CreationError::OsError(msg),
+        }
+    }
+
+    fn into_context_error(self) -> ContextError {
+        match self {
+            MockFailure::ContextLost => ContextError::ContextLost,
+            MockFailure::NoAvailablePixelFormat => {
+                ContextError::OsError("no available pixel format".to_string())
+            }
+            MockFailure::OsError(msg) => ContextError::OsError(msg),
+        }
+    }
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the calls made so far, failed ones included, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Forgets the calls made so far.
+    pub fn clear_calls(&self) {
+        self.state.lock().unwrap().calls.clear()
+    }
+
+    /// Makes the next `op` fail with `failure`.
+    ///
+    /// Failures queued for the same call are used up in order.
+    pub fn fail_next(&self, op: MockOp, failure: MockFailure) {
+        self.state.lock().unwrap().failures.push((op, failure))
+    }
+
+    /// Loses every context built so far: making them current and swapping
+    /// their buffers now fails with [`ContextError::ContextLost`]. Contexts
+    /// built afterwards work.
+    ///
+    /// [`ContextError::ContextLost`]: ../enum.ContextError.html#variant.ContextLost
+    pub fn lose_contexts(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.lost_below = state.next_id;
+    }
+
+    /// Records the creation of a context, returning its id.
+    pub(crate) fn create(&self) -> Result<u64, CreationError> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(MockCall::Create);
+        state.take_failure(MockOp::Create).map_err(MockFailure::into_creation_error)?;
+        state.next_id += 1;
+        Ok(state.next_id - 1)
+    }
+
+    /// Records a fallible call on the context `id`.
+    pub(crate) fn call(&self, id: u64, call: MockCall, op: MockOp) -> Result<(), ContextError> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(call);
+        if id < state.lost_below && matches!(op, MockOp::MakeCurrent | MockOp::SwapBuffers) {
+            return Err(ContextError::ContextLost);
+        }
+        state.take_failure(op).map_err(MockFailure::into_context_error)
+    }
+
+    /// Records a call that can't fail.
+    pub(crate) fn record(&self, call: MockCall) {
+        self.state.lock().unwrap().calls.push(call)
+    }
+
+    /// Returns the address of the record, shared by the clones of the backend.
+    pub(crate) fn addr(&self) -> usize {
+        Arc::as_ptr(&self.state) as usize
+    }
+}
+
+impl MockState {
+    fn take_failure(&mut self, op: MockOp) -> Result<(), MockFailure> {
+        match self.failures.iter().position(|&(failing, _)| failing == op) {
+            Some(index) => Err(self.failures.remove(index).1),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, T: ContextCurrentState> ContextBuilder<'a, T> {
+    /// Builds a mock context of the given size, recording its calls into
+    /// `backend`. See the [`mock`] module.
+    ///
+    /// It can only share objects with other mock contexts.
+    ///
+    /// [`mock`]: mock/index.html
+    pub fn build_mock(
+        self,
+        backend: &MockBackend,
+        size: dpi::PhysicalSize<u32>,
+    ) -> Result<RawContext<NotCurrent>, CreationError> {
+        self.build_with(|pf_reqs, gl_attr| {
+            match gl_attr.sharing {
+                Some(platform_impl::Context::Mock(_)) | None => (),
+                Some(_) => {
+                    let msg = "Cannot share a mock context with a non-mock context";
+                    return Err(CreationError::IncompatibleShareContext(msg.into()));
+                }
+            }
+            let context = MockContext::new(pf_reqs, gl_attr, size, backend.clone())?;
+            Ok(((), platform_impl::Context::Mock(context)))
+        })
+        .map(|(window, context)| RawContext { context, window })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PossiblyCurrent, RawContext};
+
+    fn build(backend: &MockBackend) -> RawContext<PossiblyCurrent> {
+        let context = ContextBuilder::new().build_mock(backend, dpi::PhysicalSize::new(8, 8));
+        unsafe { context.unwrap().make_current().unwrap() }
+    }
+
+    #[test]
+    fn records_calls() {
+        let backend = MockBackend::new();
+        let context = build(&backend);
+        context.swap_buffers().unwrap();
+        context.swap_buffers_with_damage(&[Rect { x: 0, y: 0, width: 1, height: 1 }]).unwrap();
+        context.try_resize(dpi::PhysicalSize::new(4, 2)).unwrap();
+        drop(context);
+
+        assert_eq!(
+            backend.calls(),
+            [
+                MockCall::Create,
+                MockCall::MakeCurrent,
+                MockCall::SwapBuffers,
+                MockCall::SwapBuffersWithDamage(vec![Rect { x: 0, y: 0, width: 1, height: 1 }]),
+                MockCall::Resize { width: 4, height: 2 },
+                MockCall::Destroy,
+            ]
+        );
+        backend.clear_calls();
+        assert!(backend.calls().is_empty());
+    }
+
+    #[test]
+    fn fail_next() {
+        let backend = MockBackend::new();
+        backend.fail_next(MockOp::Create, MockFailure::NoAvailablePixelFormat);
+        let result = ContextBuilder::new().build_mock(&backend, dpi::PhysicalSize::new(8, 8));
+        assert!(matches!(result, Err(CreationError::NoAvailablePixelFormat)));
+
+        let context = build(&backend);
+        backend.fail_next(MockOp::Resize, MockFailure::OsError("resize".to_string()));
+        backend.fail_next(MockOp::SwapBuffers, MockFailure::ContextLost);
+        backend.fail_next(MockOp::SwapBuffers, MockFailure::OsError("swap".to_string()));
+        assert!(matches!(context.swap_buffers(), Err(ContextError::ContextLost)));
+        assert!(matches!(context.swap_buffers(), Err(ContextError::OsError(msg)) if msg == "swap"));
+        assert!(context.swap_buffers().is_ok());
+        assert!(matches!(
+            context.try_resize(dpi::PhysicalSize::new(4, 2)),
+            Err(ContextError::OsError(msg)) if msg == "resize"
+        ));
+        assert!(context.try_resize(dpi::PhysicalSize::new(4, 2)).is_ok());
+    }
+
+    #[test]
+    fn lose_contexts() {
+        let backend = MockBackend::new();
+        let lost = build(&backend);
+        backend.lose_contexts();
+        let built_after = build(&backend);
+
+        assert!(matches!(lost.swap_buffers(), Err(ContextError::ContextLost)));
+        assert!(matches!(unsafe { lost.make_current() }, Err((_, ContextError::ContextLost))));
+        assert!(built_after.swap_buffers().is_ok());
+    }
+
+    #[test]
+    fn current_per_backend() {
+        let first = MockBackend::new();
+        let second = MockBackend::new();
+        let first_context = build(&first);
+        let second_context = build(&second);
+
+        // both are the first context of their backend
+        assert!(!first_context.is_current());
+        assert!(second_context.is_current());
+
+        drop(first_context);
+        assert!(second_context.is_current());
+    }
+}
@@ -20,8 +20,9 @@ pub mod unix;
 pub mod windows;
 /// Platform-specific methods for event loops independent from the application
 /// lifetime.
-pub mod run_return {
-    #![cfg(any(
+#[cfg(all(
+    feature = "winit",
+    any(
         target_os = "windows",
         target_os = "macos",
         target_os = "linux",
@@ -30,7 +31,9 @@ pub mod run_return {
         target_os = "netbsd",
         target_os = "openbsd",
         target_os = "android",
-    ))]
+    ),
+))]
+pub mod run_return {
     pub use winit::platform::run_return::*;
 }
 
@@ -7,14 +7,36 @@
 ))]
 
 use crate::platform::ContextTraitExt;
-pub use crate::platform_impl::{HeadlessContextExt, RawContextExt, RawHandle};
+#[cfg(feature = "winit")]
+pub use crate::platform_impl::WindowedContextExt;
+#[cfg(feature = "x11")]
+pub use crate::platform_impl::X11Visual;
+pub use crate::platform_impl::{
+    ContextBuilderExtUnix, ContextWrapperExtUnix, HeadlessContextExt, HeadlessDisplay,
+    OffscreenBackend, OffscreenFailures, OffscreenReport, RawContextExt, RawHandle,
+};
 use crate::{Context, ContextCurrentState};
 pub use glutin_egl_sys::EGLContext;
 #[cfg(feature = "x11")]
 pub use glutin_glx_sys::GLXContext;
 
+#[cfg(feature = "winit")]
 pub use winit::platform::unix::*;
 
+/// The X11 types winit provides when glutin is built with it.
+#[cfg(all(feature = "x11", not(feature = "winit")))]
+pub mod x11 {
+    pub use crate::platform_impl::{XConnection, XError, XNotSupported};
+
+    /// The Xlib bindings used by glutin.
+    pub mod ffi {
+        pub use glutin_glx_sys::error::OpenError;
+        pub use glutin_glx_sys::xlib::*;
+        pub use glutin_glx_sys::xlib_xcb::*;
+        pub use glutin_glx_sys::xrender::*;
+    }
+}
+
 use std::os::raw;
 
 impl<T: ContextCurrentState> ContextTraitExt for Context<T> {
@@ -30,3 +52,24 @@ impl<T: ContextCurrentState> ContextTraitExt for Context<T> {
         self.context.get_egl_display()
     }
 }
+
+/// A unix-specific extension to [`Context`] giving access to its X visual.
+///
+/// [`Context`]: ../../struct.Context.html
+#[cfg(feature = "x11")]
+pub trait ContextExtUnix {
+    /// Returns the visual chosen for the context, so that windows created
+    /// outside of winit can use a matching one.
+    ///
+    /// Returns `None` if the context doesn't use X11, or if its config has no
+    /// visual, which can happen for headless EGL contexts.
+    fn x11_visual_info(&self) -> Option<x11::ffi::XVisualInfo>;
+}
+
+#[cfg(feature = "x11")]
+impl<T: ContextCurrentState> ContextExtUnix for Context<T> {
+    #[inline]
+    fn x11_visual_info(&self) -> Option<x11::ffi::XVisualInfo> {
+        self.context.get_x11_visual_info()
+    }
+}
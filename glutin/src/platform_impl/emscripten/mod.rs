@@ -1,10 +1,12 @@
 #![cfg(target_os = "emscripten")]
 
 use crate::{
-    Api, ContextError, CreationError, GlAttributes, GlRequest, PixelFormat, PixelFormatRequirements,
+    Api, ContextError, ContextPriority, CreationError, GlAttributes, GlRequest, PixelFormat,
+    PixelFormatRequirements, RendererInfo,
 };
 
 use glutin_emscripten_sys as ffi;
+use raw_window_handle::RawWindowHandle;
 use winit;
 use winit::dpi;
 use winit::event_loop::EventLoopWindowTarget;
@@ -63,6 +65,18 @@ impl Context {
         Ok((win, Context::Window(context)))
     }
 
+    #[inline]
+    pub unsafe fn new_from_raw_window_handle(
+        _handle: RawWindowHandle,
+        _size: dpi::PhysicalSize<u32>,
+        _pf_reqs: &PixelFormatRequirements,
+        _gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        Err(CreationError::NotSupported(
+            "building from a raw window handle is not supported on this platform".to_string(),
+        ))
+    }
+
     #[inline]
     pub fn new_headless<T>(
         el: &EventLoopWindowTarget<T>,
@@ -90,6 +104,11 @@ impl Context {
         Api::WebGl
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        None
+    }
+
     #[inline]
     pub unsafe fn raw_handle(&self) -> ffi::EMSCRIPTEN_WEBGL_CONTEXT_HANDLE {
         match self {
@@ -125,9 +144,9 @@ impl Context {
     }
 
     #[inline]
-    pub fn resize(&self, _width: u32, _height: u32) {
+    pub fn resize(&self, _width: u32, _height: u32) -> Result<(), ContextError> {
         match *self {
-            Context::Window(_) => (), // TODO: ?
+            Context::Window(_) => Ok(()), // TODO: ?
             Context::WindowedContext(_, _) => unreachable!(),
         }
     }
@@ -158,6 +177,36 @@ impl Context {
         false
     }
 
+    #[inline]
+    pub fn set_damage_region(&self, _rects: &[Rect]) -> Result<(), ContextError> {
+        Err(ContextError::FunctionUnavailable)
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        &[]
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        None
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        RendererInfo::from_gl(|addr| self.get_proc_address(addr))
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         // FIXME: this is a dummy pixel format
@@ -171,6 +220,7 @@ impl Context {
             double_buffer: true,
             multisampling: None,
             srgb: true,
+            transparency: false,
         }
     }
 }
@@ -1,7 +1,7 @@
 #![cfg(target_os = "macos")]
 use crate::{
-    ContextError, CreationError, GlAttributes, PixelFormat, PixelFormatRequirements, Rect,
-    Robustness,
+    ContextError, ContextPriority, CreationError, GlAttributes, PixelFormat,
+    PixelFormatRequirements, Rect, RendererInfo, Robustness,
 };
 
 use cgl::{kCGLCECrashOnRemovedFunctions, kCGLCPSurfaceOpacity, CGLEnable, CGLSetParameter};
@@ -12,7 +12,10 @@ use core_foundation::base::TCFType;
 use core_foundation::bundle::{CFBundleGetBundleWithIdentifier, CFBundleGetFunctionPointerForName};
 use core_foundation::string::CFString;
 use objc::runtime::{BOOL, NO};
+use raw_window_handle::RawWindowHandle;
 
+#[cfg(feature = "mock")]
+use crate::api::mock::MockContext;
 use crate::platform::macos::WindowExtMacOS;
 use winit;
 use winit::dpi;
@@ -29,6 +32,8 @@ mod helpers;
 pub enum Context {
     WindowedContext(WindowedContext),
     HeadlessContext(HeadlessContext),
+    #[cfg(feature = "mock")]
+    Mock(MockContext),
 }
 
 #[derive(Debug)]
@@ -51,6 +56,12 @@ impl Context {
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
     ) -> Result<(Window, Self), CreationError> {
+        #[cfg(feature = "mock")]
+        if let Some(&Context::Mock(_)) = gl_attr.sharing {
+            let msg = "Cannot share a mock context with a non-mock context";
+            return Err(CreationError::IncompatibleShareContext(msg.into()));
+        }
+
         let transparent = wb.window.transparent;
         let win = wb.build(el)?;
 
@@ -115,6 +126,7 @@ impl Context {
                         None
                     },
                     srgb: true,
+                    transparency: transparent,
                 }
             };
 
@@ -141,6 +153,18 @@ impl Context {
         }
     }
 
+    #[inline]
+    pub unsafe fn new_from_raw_window_handle(
+        _handle: RawWindowHandle,
+        _size: dpi::PhysicalSize<u32>,
+        _pf_reqs: &PixelFormatRequirements,
+        _gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        Err(CreationError::NotSupported(
+            "building from a raw window handle is not supported on this platform".to_string(),
+        ))
+    }
+
     #[inline]
     pub fn new_headless<T>(
         _el: &EventLoopWindowTarget<T>,
@@ -173,9 +197,14 @@ impl Context {
         Ok(Context::HeadlessContext(headless))
     }
 
-    pub fn resize(&self, _width: u32, _height: u32) {
+    pub fn resize(&self, _width: u32, _height: u32) -> Result<(), ContextError> {
         match *self {
-            Context::WindowedContext(ref c) => unsafe { c.context.update() },
+            Context::WindowedContext(ref c) => {
+                unsafe { c.context.update() };
+                Ok(())
+            }
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.resize(_width, _height),
             _ => unreachable!(),
         }
     }
@@ -191,12 +220,18 @@ impl Context {
                 let _: () = msg_send![*c.context, update];
                 c.context.makeCurrentContext();
             }
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => return c.make_current(),
         }
         Ok(())
     }
 
     #[inline]
     pub unsafe fn make_not_current(&self) -> Result<(), ContextError> {
+        #[cfg(feature = "mock")]
+        if let Context::Mock(ref c) = *self {
+            return c.make_not_current();
+        }
         if self.is_current() {
             match *self {
                 Context::WindowedContext(ref c) => {
@@ -207,6 +242,8 @@ impl Context {
                     let _: () = msg_send![*c.context, update];
                     NSOpenGLContext::clearCurrentContext(nil);
                 }
+                #[cfg(feature = "mock")]
+                Context::Mock(_) => unreachable!(),
             }
         }
         Ok(())
@@ -218,6 +255,8 @@ impl Context {
             let context = match *self {
                 Context::WindowedContext(ref c) => *c.context,
                 Context::HeadlessContext(ref c) => *c.context,
+                #[cfg(feature = "mock")]
+                Context::Mock(ref c) => return c.is_current(),
             };
 
             let pool = NSAutoreleasePool::new(nil);
@@ -234,6 +273,10 @@ impl Context {
     }
 
     pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
+        #[cfg(feature = "mock")]
+        if let Context::Mock(ref c) = *self {
+            return c.get_proc_address(addr);
+        }
         let symbol_name: CFString = FromStr::from_str(addr).unwrap();
         let framework_name: CFString = FromStr::from_str("com.apple.opengl").unwrap();
         let framework =
@@ -254,24 +297,86 @@ impl Context {
                     let _: () = msg_send![pool, release];
                 }
                 Context::HeadlessContext(_) => unreachable!(),
+                #[cfg(feature = "mock")]
+                Context::Mock(ref c) => return c.swap_buffers(),
             }
         }
         Ok(())
     }
 
     #[inline]
-    pub fn swap_buffers_with_damage(&self, _rects: &[Rect]) -> Result<(), ContextError> {
-        Err(ContextError::OsError("buffer damage not suported".to_string()))
+    #[cfg_attr(not(feature = "mock"), allow(unused_variables))]
+    pub fn swap_buffers_with_damage(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.swap_buffers_with_damage(rects),
+            _ => Err(ContextError::OsError("buffer damage not suported".to_string())),
+        }
     }
 
     #[inline]
     pub fn swap_buffers_with_damage_supported(&self) -> bool {
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.swap_buffers_with_damage_supported(),
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn set_damage_region(&self, _rects: &[Rect]) -> Result<(), ContextError> {
+        Err(ContextError::FunctionUnavailable)
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
         false
     }
 
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        None
+    }
+
     #[inline]
     pub fn get_api(&self) -> crate::Api {
-        crate::Api::OpenGl
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.get_api(),
+            _ => crate::Api::OpenGl,
+        }
+    }
+
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.get_api_version(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        &[]
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.priority(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.renderer_info(),
+            _ => RendererInfo::from_gl(|addr| self.get_proc_address(addr)),
+        }
     }
 
     #[inline]
@@ -279,6 +384,8 @@ impl Context {
         match *self {
             Context::WindowedContext(ref c) => c.pixel_format.clone(),
             Context::HeadlessContext(_) => unreachable!(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.get_pixel_format(),
         }
     }
 
@@ -287,6 +394,8 @@ impl Context {
         match self {
             Context::WindowedContext(c) => c.context.deref().CGLContextObj() as *mut _,
             Context::HeadlessContext(c) => c.context.deref().CGLContextObj() as *mut _,
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => std::ptr::null_mut(),
         }
     }
 
@@ -295,6 +404,8 @@ impl Context {
         match self {
             Context::WindowedContext(w) => w.context.clone(),
             Context::HeadlessContext(h) => h.context.clone(),
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => unreachable!(),
         }
     }
 }
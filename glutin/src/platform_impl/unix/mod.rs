@@ -9,31 +9,106 @@
 #[cfg(not(any(feature = "x11", feature = "wayland")))]
 compile_error!("at least one of the 'x11' or 'wayland' features must be enabled");
 
+mod software;
 mod wayland;
 mod x11;
 
 #[cfg(feature = "x11")]
 use self::x11::X11Context;
+use crate::api::egl::EglDisplay;
+#[cfg(feature = "mock")]
+use crate::api::mock::MockContext;
 use crate::api::osmesa;
 use crate::{
-    Api, ContextCurrentState, ContextError, CreationError, GlAttributes, NotCurrent, PixelFormat,
-    PixelFormatRequirements, Rect,
+    Api, ContextCurrentState, ContextError, ContextPriority, CreationError, GlAttributes,
+    NotCurrent, PixelFormat, PixelFormatRequirements, PossiblyCurrent, Rect, RendererInfo,
 };
 #[cfg(feature = "x11")]
 pub use x11::utils as x11_utils;
 
-#[cfg(feature = "x11")]
+#[cfg(all(feature = "x11", not(feature = "winit")))]
+pub use x11::xconnection::{XConnection, XError, XNotSupported};
+
+use crate::dpi;
+#[cfg(all(feature = "x11", feature = "winit"))]
 use crate::platform::unix::x11::XConnection;
+#[cfg(feature = "winit")]
 use crate::platform::unix::EventLoopWindowTargetExtUnix;
-use winit::dpi;
-use winit::event_loop::EventLoopWindowTarget;
+use raw_window_handle::RawWindowHandle;
+#[cfg(feature = "winit")]
+use winit::event_loop::{EventLoop, EventLoopWindowTarget};
+#[cfg(feature = "winit")]
 use winit::window::{Window, WindowBuilder};
 
-use std::marker::PhantomData;
 use std::os::raw;
 #[cfg(feature = "x11")]
 use std::sync::Arc;
 
+/// The connection to the display server that headless contexts are built on,
+/// as taken by [`HeadlessContextExt`].
+///
+/// With the `winit` feature, it can be converted from an event loop, so that
+/// e.g. `build_surfaceless(&event_loop)` works. Otherwise it wraps an X11
+/// connection or a Wayland display.
+///
+/// [`HeadlessContextExt`]: trait.HeadlessContextExt.html
+#[derive(Debug, Clone)]
+pub struct HeadlessDisplay {
+    inner: HeadlessDisplayInner,
+}
+
+#[derive(Debug, Clone)]
+enum HeadlessDisplayInner {
+    #[cfg(feature = "x11")]
+    X11(Arc<XConnection>),
+    #[cfg(feature = "wayland")]
+    Wayland(*const wayland::wl_display),
+}
+
+impl HeadlessDisplay {
+    /// Builds contexts on the given X11 connection.
+    #[inline]
+    #[cfg(feature = "x11")]
+    pub fn x11(xconn: Arc<XConnection>) -> Self {
+        HeadlessDisplay { inner: HeadlessDisplayInner::X11(xconn) }
+    }
+
+    /// Builds contexts on the given Wayland display.
+    ///
+    /// # Safety
+    ///
+    /// `display` must be a valid `wl_display`, outliving the contexts built
+    /// on it.
+    #[inline]
+    #[cfg(feature = "wayland")]
+    pub unsafe fn wayland(display: *const wayland::wl_display) -> Self {
+        HeadlessDisplay { inner: HeadlessDisplayInner::Wayland(display) }
+    }
+}
+
+#[cfg(feature = "winit")]
+impl<'a, T> From<&'a EventLoopWindowTarget<T>> for HeadlessDisplay {
+    fn from(el: &'a EventLoopWindowTarget<T>) -> Self {
+        #[cfg(feature = "wayland")]
+        if let Some(display) = el.wayland_display() {
+            return unsafe { HeadlessDisplay::wayland(display as *const _) };
+        }
+        #[cfg(feature = "x11")]
+        if let Some(xconn) = el.xlib_xconnection() {
+            return HeadlessDisplay::x11(xconn);
+        }
+        panic!("glutin was not compiled with support for this display server")
+    }
+}
+
+#[cfg(feature = "winit")]
+impl<'a, T> From<&'a EventLoop<T>> for HeadlessDisplay {
+    #[inline]
+    fn from(el: &'a EventLoop<T>) -> Self {
+        HeadlessDisplay::from(&**el)
+    }
+}
+
 /// Context handles available on Unix-like platforms.
 #[derive(Clone, Debug)]
 pub enum RawHandle {
@@ -60,6 +135,10 @@ pub enum Context {
     #[cfg(feature = "wayland")]
     Wayland(wayland::Context),
     OsMesa(osmesa::OsMesaContext),
+    #[cfg(feature = "mock")]
+    Mock(MockContext),
+    #[cfg(feature = "winit")]
+    Software(software::Context),
 }
 
 impl Context {
@@ -70,7 +149,7 @@ impl Context {
                     Context::OsMesa(_) => Ok(()),
                     _ => {
                         let msg = "Cannot share an OSMesa context with a non-OSMesa context";
-                        return Err(CreationError::PlatformSpecific(msg.into()));
+                        return Err(CreationError::IncompatibleShareContext(msg.into()));
                     }
                 },
                 #[cfg(feature = "x11")]
@@ -78,7 +157,7 @@ impl Context {
                     Context::X11(_) => Ok(()),
                     _ => {
                         let msg = "Cannot share an X11 context with a non-X11 context";
-                        return Err(CreationError::PlatformSpecific(msg.into()));
+                        return Err(CreationError::IncompatibleShareContext(msg.into()));
                     }
                 },
                 #[cfg(feature = "wayland")]
@@ -86,7 +165,7 @@ impl Context {
                     Context::Wayland(_) => Ok(()),
                     _ => {
                         let msg = "Cannot share a Wayland context with a non-Wayland context";
-                        return Err(CreationError::PlatformSpecific(msg.into()));
+                        return Err(CreationError::IncompatibleShareContext(msg.into()));
                     }
                 },
             }
@@ -96,6 +175,7 @@ impl Context {
     }
 
     #[inline]
+    #[cfg(feature = "winit")]
     pub fn new_windowed<T>(
         wb: WindowBuilder,
         el: &EventLoopWindowTarget<T>,
@@ -127,42 +207,125 @@ impl Context {
     }
 
     #[inline]
+    #[cfg_attr(not(feature = "wayland"), allow(unused_variables))]
+    pub unsafe fn new_from_raw_window_handle(
+        handle: RawWindowHandle,
+        size: dpi::PhysicalSize<u32>,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        match handle {
+            #[cfg(feature = "wayland")]
+            RawWindowHandle::Wayland(handle) => {
+                Context::is_compatible(&gl_attr.sharing, ContextType::Wayland)?;
+                let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+                    Context::Wayland(ref ctx) => ctx,
+                    _ => unreachable!(),
+                });
+                wayland::Context::new_raw_context(
+                    handle.display as *const _,
+                    handle.surface,
+                    size.width,
+                    size.height,
+                    false,
+                    pf_reqs,
+                    &gl_attr,
+                )
+                .map(Context::Wayland)
+            }
+            #[cfg(feature = "x11")]
+            RawWindowHandle::Xlib(handle) => {
+                let xconn = if handle.display.is_null() {
+                    x11_utils::open_xconnection()?
+                } else {
+                    x11_utils::foreign_xconnection(handle.display as *mut _)?
+                };
+                Self::new_raw_x11_context(xconn, handle.window, pf_reqs, gl_attr)
+            }
+            #[cfg(feature = "x11")]
+            RawWindowHandle::Xcb(handle) => Self::new_raw_xcb_context(
+                handle.connection,
+                handle.window,
+                handle.visual_id,
+                pf_reqs,
+                gl_attr,
+            ),
+            _ => Err(CreationError::NotSupported(
+                "window handle not supported by this build of glutin".to_string(),
+            )),
+        }
+    }
+
+    #[cfg(feature = "x11")]
+    fn new_raw_x11_context(
+        xconn: Arc<XConnection>,
+        xwin: raw::c_ulong,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        Context::is_compatible(&gl_attr.sharing, ContextType::X11)?;
+        let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+            Context::X11(ref ctx) => ctx,
+            _ => unreachable!(),
+        });
+        x11::Context::new_raw_context(xconn, xwin, pf_reqs, &gl_attr).map(Context::X11)
+    }
+
+    #[cfg(feature = "x11")]
+    unsafe fn new_raw_xcb_context(
+        connection: *mut raw::c_void,
+        xwin: u32,
+        visual_id: u32,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        Context::is_compatible(&gl_attr.sharing, ContextType::X11)?;
+        let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+            Context::X11(ref ctx) => ctx,
+            _ => unreachable!(),
+        });
+        x11::Context::new_raw_xcb_context(connection, xwin, visual_id, pf_reqs, &gl_attr)
+            .map(Context::X11)
+    }
+
+    #[inline]
+    #[cfg(feature = "winit")]
     pub fn new_headless<T>(
         el: &EventLoopWindowTarget<T>,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
         size: dpi::PhysicalSize<u32>,
     ) -> Result<Self, CreationError> {
-        Self::new_headless_impl(el, pf_reqs, gl_attr, Some(size))
+        Self::new_headless_impl(&el.into(), pf_reqs, gl_attr, Some(size))
     }
 
-    pub fn new_headless_impl<T>(
-        el: &EventLoopWindowTarget<T>,
+    pub fn new_headless_impl(
+        display: &HeadlessDisplay,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
         size: Option<dpi::PhysicalSize<u32>>,
     ) -> Result<Self, CreationError> {
-        #[cfg(feature = "wayland")]
-        if el.is_wayland() {
-            Context::is_compatible(&gl_attr.sharing, ContextType::Wayland)?;
-            let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
-                Context::Wayland(ref ctx) => ctx,
-                _ => unreachable!(),
-            });
-            return wayland::Context::new_headless(&el, pf_reqs, &gl_attr, size)
-                .map(|ctx| Context::Wayland(ctx));
-        }
-        #[cfg(feature = "x11")]
-        if el.is_x11() {
-            Context::is_compatible(&gl_attr.sharing, ContextType::X11)?;
-            let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
-                Context::X11(ref ctx) => ctx,
-                _ => unreachable!(),
-            });
-            return x11::Context::new_headless(&el, pf_reqs, &gl_attr, size)
-                .map(|ctx| Context::X11(ctx));
+        match display.inner {
+            #[cfg(feature = "wayland")]
+            HeadlessDisplayInner::Wayland(display_ptr) => {
+                Context::is_compatible(&gl_attr.sharing, ContextType::Wayland)?;
+                let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+                    Context::Wayland(ref ctx) => ctx,
+                    _ => unreachable!(),
+                });
+                wayland::Context::new_headless(display_ptr, pf_reqs, &gl_attr, size)
+                    .map(Context::Wayland)
+            }
+            #[cfg(feature = "x11")]
+            HeadlessDisplayInner::X11(ref xconn) => {
+                Context::is_compatible(&gl_attr.sharing, ContextType::X11)?;
+                let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+                    Context::X11(ref ctx) => ctx,
+                    _ => unreachable!(),
+                });
+                x11::Context::new_headless(xconn, pf_reqs, &gl_attr, size).map(Context::X11)
+            }
         }
-        panic!("glutin was not compiled with support for this display server")
     }
 
     #[inline]
@@ -173,6 +336,10 @@ impl Context {
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.make_current(),
             Context::OsMesa(ref ctx) => ctx.make_current(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.make_current(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.make_current(),
         }
     }
 
@@ -184,6 +351,10 @@ impl Context {
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.make_not_current(),
             Context::OsMesa(ref ctx) => ctx.make_not_current(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.make_not_current(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.make_not_current(),
         }
     }
 
@@ -195,6 +366,10 @@ impl Context {
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.is_current(),
             Context::OsMesa(ref ctx) => ctx.is_current(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.is_current(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.is_current(),
         }
     }
 
@@ -206,6 +381,25 @@ impl Context {
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.get_api(),
             Context::OsMesa(ref ctx) => ctx.get_api(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.get_api(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.get_api(),
+        }
+    }
+
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.get_api_version(),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.get_api_version(),
+            Context::OsMesa(ref ctx) => ctx.get_api_version(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.get_api_version(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.get_api_version(),
         }
     }
 
@@ -220,6 +414,10 @@ impl Context {
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => RawHandle::Egl(ctx.raw_handle()),
             Context::OsMesa(ref ctx) => RawHandle::Egl(ctx.raw_handle()),
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => RawHandle::Egl(std::ptr::null()),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => RawHandle::Egl(ctx.raw_handle()),
         }
     }
 
@@ -235,14 +433,40 @@ impl Context {
     }
 
     #[inline]
-    pub fn resize(&self, width: u32, height: u32) {
-        #![allow(unused)]
+    pub fn egl_display(&self) -> Option<EglDisplay> {
         match *self {
             #[cfg(feature = "x11")]
-            Context::X11(_) => (),
+            Context::X11(ref ctx) => ctx.egl_display(),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.egl_display(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.resize(width, height),
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.resize(width, height),
-            _ => unreachable!(),
+            Context::OsMesa(ref ctx) => ctx.resize(width, height),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.resize(width, height),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.resize(width, height),
+        }
+    }
+
+    #[inline]
+    #[cfg_attr(not(feature = "wayland"), allow(unused_variables))]
+    pub fn resize_with_scale(&self, width: u32, height: u32, scale: i32, dx: i32, dy: i32) {
+        match *self {
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.resize_with_scale(width, height, scale, dx, dy),
+            _ => {
+                let _ = self.resize(width, height);
+            }
         }
     }
 
@@ -254,6 +478,10 @@ impl Context {
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.get_proc_address(addr),
             Context::OsMesa(ref ctx) => ctx.get_proc_address(addr),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.get_proc_address(addr),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.get_proc_address(addr),
         }
     }
 
@@ -264,6 +492,10 @@ impl Context {
             Context::X11(ref ctx) => ctx.swap_buffers(),
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.swap_buffers(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.swap_buffers(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.swap_buffers(),
             _ => unreachable!(),
         }
     }
@@ -275,6 +507,10 @@ impl Context {
             Context::X11(ref ctx) => ctx.swap_buffers_with_damage(rects),
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.swap_buffers_with_damage(rects),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.swap_buffers_with_damage(rects),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.swap_buffers_with_damage(rects),
             _ => unreachable!(),
         }
     }
@@ -286,10 +522,102 @@ impl Context {
             Context::X11(ref ctx) => ctx.swap_buffers_with_damage_supported(),
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.swap_buffers_with_damage_supported(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.swap_buffers_with_damage_supported(),
+            #[cfg(feature = "winit")]
+            Context::Software(_) => true,
             _ => unreachable!(),
         }
     }
 
+    #[inline]
+    pub fn set_damage_region(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.set_damage_region(rects),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.set_damage_region(rects),
+            Context::OsMesa(_) => Err(ContextError::FunctionUnavailable),
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => Err(ContextError::FunctionUnavailable),
+            #[cfg(feature = "winit")]
+            Context::Software(_) => Err(ContextError::FunctionUnavailable),
+        }
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.set_damage_region_supported(),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.set_damage_region_supported(),
+            Context::OsMesa(_) => false,
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => false,
+            #[cfg(feature = "winit")]
+            Context::Software(_) => false,
+        }
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.buffer_age(),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.buffer_age(),
+            Context::OsMesa(_) => None,
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => None,
+            #[cfg(feature = "winit")]
+            Context::Software(_) => None,
+        }
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.platform_extensions(),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.platform_extensions(),
+            Context::OsMesa(_) => &[],
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => &[],
+            #[cfg(feature = "winit")]
+            Context::Software(_) => &[],
+        }
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.priority(),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.priority(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.priority(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        match *self {
+            #[cfg(feature = "x11")]
+            Context::X11(ref ctx) => ctx.renderer_info(),
+            #[cfg(feature = "wayland")]
+            Context::Wayland(ref ctx) => ctx.renderer_info(),
+            Context::OsMesa(ref ctx) => ctx.renderer_info(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.renderer_info(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.renderer_info(),
+        }
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         match *self {
@@ -297,11 +625,135 @@ impl Context {
             Context::X11(ref ctx) => ctx.get_pixel_format(),
             #[cfg(feature = "wayland")]
             Context::Wayland(ref ctx) => ctx.get_pixel_format(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref ctx) => ctx.get_pixel_format(),
+            #[cfg(feature = "winit")]
+            Context::Software(ref ctx) => ctx.get_pixel_format(),
             _ => unreachable!(),
         }
     }
+
+    #[inline]
+    #[cfg(feature = "x11")]
+    pub fn get_x11_visual_info(&self) -> Option<glutin_glx_sys::XVisualInfo> {
+        match *self {
+            Context::X11(ref ctx) => ctx.get_visual_info(),
+            _ => None,
+        }
+    }
+}
+
+/// The ways [`HeadlessContextExt::build_offscreen`] can build a context.
+///
+/// [`HeadlessContextExt::build_offscreen`]:
+/// trait.HeadlessContextExt.html#tymethod.build_offscreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffscreenBackend {
+    /// See [`HeadlessContextExt::build_surfaceless`].
+    ///
+    /// [`HeadlessContextExt::build_surfaceless`]:
+    /// trait.HeadlessContextExt.html#tymethod.build_surfaceless
+    Surfaceless,
+    /// See [`HeadlessContextExt::build_pbuffer`].
+    ///
+    /// [`HeadlessContextExt::build_pbuffer`]:
+    /// trait.HeadlessContextExt.html#tymethod.build_pbuffer
+    PBuffer,
+    /// See [`HeadlessContextExt::build_osmesa`].
+    ///
+    /// [`HeadlessContextExt::build_osmesa`]:
+    /// trait.HeadlessContextExt.html#tymethod.build_osmesa
+    OsMesa,
+}
+
+impl OffscreenBackend {
+    /// The order in which backends should usually be tried: surfaceless, then
+    /// pbuffers, then OSMesa.
+    pub const DEFAULT_ORDER: &'static [OffscreenBackend] =
+        &[OffscreenBackend::Surfaceless, OffscreenBackend::PBuffer, OffscreenBackend::OsMesa];
+
+    /// The environment variable overriding the order passed to
+    /// [`HeadlessContextExt::build_offscreen`]. It holds a comma-separated
+    /// list of `surfaceless`, `pbuffer` and `osmesa`, e.g. `osmesa` to force
+    /// OSMesa on CI. An empty list is ignored.
+    ///
+    /// [`HeadlessContextExt::build_offscreen`]:
+    /// trait.HeadlessContextExt.html#tymethod.build_offscreen
+    pub const ENV_VAR: &'static str = "GLUTIN_OFFSCREEN_BACKENDS";
+
+    fn from_env() -> Result<Option<Vec<OffscreenBackend>>, CreationError> {
+        match std::env::var(Self::ENV_VAR) {
+            Ok(order) => Self::parse(&order),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Parses the value of [`ENV_VAR`]. Empty entries and repeated backends
+    /// are skipped, and `None` is returned if no backend is left, as if the
+    /// variable was unset.
+    ///
+    /// [`ENV_VAR`]: #associatedconstant.ENV_VAR
+    fn parse(order: &str) -> Result<Option<Vec<OffscreenBackend>>, CreationError> {
+        let mut backends = Vec::new();
+        for name in order.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let backend = match name {
+                "surfaceless" => OffscreenBackend::Surfaceless,
+                "pbuffer" => OffscreenBackend::PBuffer,
+                "osmesa" => OffscreenBackend::OsMesa,
+                name => {
+                    return Err(CreationError::NotSupported(format!(
+                        "unknown offscreen backend `{}` in `{}`",
+                        name,
+                        Self::ENV_VAR
+                    )))
+                }
+            };
+            if !backends.contains(&backend) {
+                backends.push(backend);
+            }
+        }
+        Ok(Some(backends).filter(|backends| !backends.is_empty()))
+    }
 }
 
+/// What happened while building a context with
+/// [`HeadlessContextExt::build_offscreen`].
+///
+/// [`HeadlessContextExt::build_offscreen`]:
+/// trait.HeadlessContextExt.html#tymethod.build_offscreen
+#[derive(Debug)]
+pub struct OffscreenReport {
+    /// The backend that built the context.
+    pub backend: OffscreenBackend,
+    /// The backends that were tried before `backend`, along with why they
+    /// failed.
+    pub failures: Vec<(OffscreenBackend, CreationError)>,
+}
+
+/// Why each backend tried by [`HeadlessContextExt::build_offscreen`] failed,
+/// in order, when none of them could build a context. It's returned in a
+/// [`CreationError::NoBackendAvailable`].
+///
+/// [`HeadlessContextExt::build_offscreen`]:
+/// trait.HeadlessContextExt.html#tymethod.build_offscreen
+/// [`CreationError::NoBackendAvailable`]:
+/// ../../enum.CreationError.html#variant.NoBackendAvailable
+#[derive(Debug)]
+pub struct OffscreenFailures(pub Vec<(OffscreenBackend, CreationError)>);
+
+impl std::fmt::Display for OffscreenFailures {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        formatter.write_str("Every offscreen backend failed")?;
+        for (index, (backend, err)) in self.0.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { "; " };
+            write!(formatter, "{}{:?}: {}", separator, backend, err)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OffscreenFailures {}
+
 /// A unix-specific extension to the [`ContextBuilder`] which allows building
 /// unix-specific headless contexts.
 ///
@@ -328,12 +780,53 @@ pub trait HeadlessContextExt {
     /// requested feature.
     ///
     /// [`Context`]: struct.Context.html
-    fn build_surfaceless<TE>(
+    fn build_surfaceless<D: Into<HeadlessDisplay>>(
         self,
-        el: &EventLoopWindowTarget<TE>,
+        display: D,
     ) -> Result<crate::Context<NotCurrent>, CreationError>
     where
         Self: Sized;
+
+    /// Builds a context rendering to a pbuffer of the given size, like
+    /// [`ContextBuilder::build_headless`] but without needing an event loop.
+    ///
+    /// [`ContextBuilder::build_headless`]:
+    /// ../../struct.ContextBuilder.html#method.build_headless
+    fn build_pbuffer<D: Into<HeadlessDisplay>>(
+        self,
+        display: D,
+        size: dpi::PhysicalSize<u32>,
+    ) -> Result<crate::Context<NotCurrent>, CreationError>
+    where
+        Self: Sized;
+
+    /// Builds an offscreen context by trying each backend of `order` in turn,
+    /// stopping at the first one which works.
+    ///
+    /// [`OffscreenBackend::DEFAULT_ORDER`] is what most applications want.
+    /// If the [`OffscreenBackend::ENV_VAR`] environment variable is set, it
+    /// takes precedence over `order`. `size` is ignored by the surfaceless
+    /// backend.
+    ///
+    /// If every backend fails, a [`CreationError::NoBackendAvailable`]
+    /// holding an [`OffscreenFailures`], with each backend's error, is
+    /// returned.
+    ///
+    /// [`OffscreenBackend::DEFAULT_ORDER`]:
+    /// enum.OffscreenBackend.html#associatedconstant.DEFAULT_ORDER
+    /// [`OffscreenBackend::ENV_VAR`]:
+    /// enum.OffscreenBackend.html#associatedconstant.ENV_VAR
+    /// [`CreationError::NoBackendAvailable`]:
+    /// ../../enum.CreationError.html#variant.NoBackendAvailable
+    /// [`OffscreenFailures`]: struct.OffscreenFailures.html
+    fn build_offscreen<D: Into<HeadlessDisplay>>(
+        self,
+        display: D,
+        size: dpi::PhysicalSize<u32>,
+        order: &[OffscreenBackend],
+    ) -> Result<(crate::Context<NotCurrent>, OffscreenReport), CreationError>
+    where
+        Self: Sized;
 }
 
 impl<'a, T: ContextCurrentState> HeadlessContextExt for crate::ContextBuilder<'a, T> {
@@ -345,33 +838,147 @@ impl<'a, T: ContextCurrentState> HeadlessContextExt for crate::ContextBuilder<'a
     where
         Self: Sized,
     {
-        let crate::ContextBuilder { pf_reqs, gl_attr } = self;
-        let gl_attr = gl_attr.map_sharing(|ctx| &ctx.context);
-        Context::is_compatible(&gl_attr.sharing, ContextType::OsMesa)?;
-        let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
-            Context::OsMesa(ref ctx) => ctx,
-            _ => unreachable!(),
-        });
-        osmesa::OsMesaContext::new(&pf_reqs, &gl_attr, size)
-            .map(|context| Context::OsMesa(context))
-            .map(|context| crate::Context { context, phantom: PhantomData })
+        self.build_with(|pf_reqs, gl_attr| {
+            Context::is_compatible(&gl_attr.sharing, ContextType::OsMesa)?;
+            let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+                Context::OsMesa(ref ctx) => ctx,
+                _ => unreachable!(),
+            });
+            osmesa::OsMesaContext::new(pf_reqs, &gl_attr, size)
+                .map(|context| ((), Context::OsMesa(context)))
+        })
+        .map(|((), context)| context)
     }
 
     #[inline]
-    fn build_surfaceless<TE>(
+    fn build_surfaceless<D: Into<HeadlessDisplay>>(
         self,
-        el: &EventLoopWindowTarget<TE>,
+        display: D,
     ) -> Result<crate::Context<NotCurrent>, CreationError>
     where
         Self: Sized,
     {
-        let crate::ContextBuilder { pf_reqs, gl_attr } = self;
-        let gl_attr = gl_attr.map_sharing(|ctx| &ctx.context);
-        Context::new_headless_impl(el, &pf_reqs, &gl_attr, None)
-            .map(|context| crate::Context { context, phantom: PhantomData })
+        let display = display.into();
+        self.build_with(|pf_reqs, gl_attr| {
+            Context::new_headless_impl(&display, pf_reqs, gl_attr, None)
+                .map(|context| ((), context))
+        })
+        .map(|((), context)| context)
+    }
+
+    #[inline]
+    fn build_pbuffer<D: Into<HeadlessDisplay>>(
+        self,
+        display: D,
+        size: dpi::PhysicalSize<u32>,
+    ) -> Result<crate::Context<NotCurrent>, CreationError>
+    where
+        Self: Sized,
+    {
+        let display = display.into();
+        self.build_with(|pf_reqs, gl_attr| {
+            Context::new_headless_impl(&display, pf_reqs, gl_attr, Some(size))
+                .map(|context| ((), context))
+        })
+        .map(|((), context)| context)
+    }
+
+    fn build_offscreen<D: Into<HeadlessDisplay>>(
+        self,
+        display: D,
+        size: dpi::PhysicalSize<u32>,
+        order: &[OffscreenBackend],
+    ) -> Result<(crate::Context<NotCurrent>, OffscreenReport), CreationError>
+    where
+        Self: Sized,
+    {
+        let display = display.into();
+        let env_order = OffscreenBackend::from_env()?;
+        let order = env_order.as_deref().unwrap_or(order);
+        if order.is_empty() {
+            return Err(CreationError::NotSupported("no offscreen backend to try".to_string()));
+        }
+
+        let mut failures = Vec::with_capacity(order.len());
+        for &backend in order {
+            let cb = self.clone();
+            let context = match backend {
+                OffscreenBackend::Surfaceless => cb.build_surfaceless(display.clone()),
+                OffscreenBackend::PBuffer => cb.build_pbuffer(display.clone(), size),
+                OffscreenBackend::OsMesa => cb.build_osmesa(size),
+            };
+
+            match context {
+                Ok(context) => return Ok((context, OffscreenReport { backend, failures })),
+                Err(err) => failures.push((backend, err)),
+            }
+        }
+
+        Err(CreationError::NoBackendAvailable(Box::new(OffscreenFailures(failures))))
+    }
+}
+
+/// A unix-specific extension to the [`ContextBuilder`] which allows building
+/// windowed contexts without a GPU.
+///
+/// [`ContextBuilder`]: ../../struct.ContextBuilder.html
+#[cfg(feature = "winit")]
+pub trait WindowedContextExt {
+    /// Builds a window and an OSMesa context rendering in software, showing
+    /// what it renders in the window when its buffers are swapped, for
+    /// machines without a working GL driver.
+    ///
+    /// The pixels are sent to the X server with `XPutImage`, or attached to
+    /// the Wayland surface as `wl_shm` buffers, so presenting is much slower
+    /// than with a hardware context. The context can't share objects with
+    /// other contexts.
+    ///
+    /// Errors can occur if OSMesa isn't installed, or if the visual of the
+    /// X11 window can't show 32-bit BGRA pixels.
+    fn build_osmesa_windowed<TE>(
+        self,
+        wb: WindowBuilder,
+        el: &EventLoopWindowTarget<TE>,
+    ) -> Result<crate::WindowedContext<NotCurrent>, CreationError>
+    where
+        Self: Sized;
+}
+
+#[cfg(feature = "winit")]
+impl<'a, T: ContextCurrentState> WindowedContextExt for crate::ContextBuilder<'a, T> {
+    #[inline]
+    fn build_osmesa_windowed<TE>(
+        self,
+        wb: WindowBuilder,
+        el: &EventLoopWindowTarget<TE>,
+    ) -> Result<crate::WindowedContext<NotCurrent>, CreationError>
+    where
+        Self: Sized,
+    {
+        self.build_with(|pf_reqs, gl_attr| {
+            software::Context::new(wb, el, pf_reqs, gl_attr)
+                .map(|(win, context)| (win, Context::Software(context)))
+        })
+        .map(|(window, context)| crate::WindowedContext { window, context })
     }
 }
 
+/// The visual of the config an X11 raw context would use, as returned by
+/// [`RawContextExt::choose_x11_visual`].
+///
+/// [`RawContextExt::choose_x11_visual`]:
+/// trait.RawContextExt.html#tymethod.choose_x11_visual
+#[cfg(feature = "x11")]
+#[derive(Debug, Clone, Copy)]
+pub struct X11Visual {
+    /// The visual, with its id, depth and screen, to create the window with.
+    pub visual_info: glutin_glx_sys::XVisualInfo,
+    /// Whether the visual isn't the default one of its screen. If so, the
+    /// window needs a colormap created for the visual with `XCreateColormap`,
+    /// and a border pixel, or `XCreateWindow` fails with `BadMatch`.
+    pub needs_colormap: bool,
+}
+
 /// A unix-specific extension for the [`ContextBuilder`] which allows
 /// assembling [`RawContext<T>`]s.
 ///
@@ -407,6 +1014,41 @@ pub trait RawContextExt {
     ) -> Result<crate::RawContext<NotCurrent>, CreationError>
     where
         Self: Sized;
+
+    /// Creates a raw context on the provided window of an XCB connection,
+    /// e.g. one made with `x11rb`.
+    ///
+    /// `visual_id` is the visual of the window, or `0` if unknown. The
+    /// context uses `EGL_EXT_platform_xcb` unless the connection belongs to
+    /// the Xlib display of another live context built from a raw window
+    /// handle, in which case GLX may be used too. Otherwise, without that extension,
+    /// [`CreationError::NotSupported`] is returned.
+    ///
+    /// [`CreationError::NotSupported`]:
+    /// ../../enum.CreationError.html#variant.NotSupported
+    ///
+    /// Unsafe behaviour might happen if you:
+    ///   - Provide us with invalid parameters.
+    ///   - The connection or xwin is destroyed before the context
+    #[cfg(feature = "x11")]
+    unsafe fn build_raw_xcb_context(
+        self,
+        connection: *mut raw::c_void,
+        xwin: u32,
+        visual_id: u32,
+    ) -> Result<crate::RawContext<NotCurrent>, CreationError>
+    where
+        Self: Sized;
+
+    /// Picks the config [`build_raw_x11_context`] would use for a window,
+    /// without creating a context, and returns its visual.
+    ///
+    /// Toolkits creating their own windows can create them with this visual,
+    /// then pass them to [`build_raw_x11_context`] with the same builder.
+    ///
+    /// [`build_raw_x11_context`]: #tymethod.build_raw_x11_context
+    #[cfg(feature = "x11")]
+    fn choose_x11_visual(&self, xconn: &Arc<XConnection>) -> Result<X11Visual, CreationError>;
 }
 
 impl<'a, T: ContextCurrentState> RawContextExt for crate::ContextBuilder<'a, T> {
@@ -422,17 +1064,24 @@ impl<'a, T: ContextCurrentState> RawContextExt for crate::ContextBuilder<'a, T>
     where
         Self: Sized,
     {
-        let crate::ContextBuilder { pf_reqs, gl_attr } = self;
-        let gl_attr = gl_attr.map_sharing(|ctx| &ctx.context);
-        Context::is_compatible(&gl_attr.sharing, ContextType::Wayland)?;
-        let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
-            Context::Wayland(ref ctx) => ctx,
-            _ => unreachable!(),
-        });
-        wayland::Context::new_raw_context(display_ptr, surface, width, height, &pf_reqs, &gl_attr)
-            .map(|context| Context::Wayland(context))
-            .map(|context| crate::Context { context, phantom: PhantomData })
-            .map(|context| crate::RawContext { context, window: () })
+        self.build_with(|pf_reqs, gl_attr| {
+            Context::is_compatible(&gl_attr.sharing, ContextType::Wayland)?;
+            let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+                Context::Wayland(ref ctx) => ctx,
+                _ => unreachable!(),
+            });
+            wayland::Context::new_raw_context(
+                display_ptr,
+                surface,
+                width,
+                height,
+                false,
+                pf_reqs,
+                &gl_attr,
+            )
+            .map(|context| ((), Context::Wayland(context)))
+        })
+        .map(|(window, context)| crate::RawContext { context, window })
     }
 
     #[inline]
@@ -445,16 +1094,219 @@ impl<'a, T: ContextCurrentState> RawContextExt for crate::ContextBuilder<'a, T>
     where
         Self: Sized,
     {
-        let crate::ContextBuilder { pf_reqs, gl_attr } = self;
-        let gl_attr = gl_attr.map_sharing(|ctx| &ctx.context);
-        Context::is_compatible(&gl_attr.sharing, ContextType::X11)?;
-        let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
-            Context::X11(ref ctx) => ctx,
-            _ => unreachable!(),
-        });
-        x11::Context::new_raw_context(xconn, xwin, &pf_reqs, &gl_attr)
-            .map(|context| Context::X11(context))
-            .map(|context| crate::Context { context, phantom: PhantomData })
-            .map(|context| crate::RawContext { context, window: () })
+        self.build_with(|pf_reqs, gl_attr| {
+            Context::new_raw_x11_context(xconn, xwin, pf_reqs, gl_attr).map(|context| ((), context))
+        })
+        .map(|(window, context)| crate::RawContext { context, window })
+    }
+
+    #[inline]
+    #[cfg(feature = "x11")]
+    unsafe fn build_raw_xcb_context(
+        self,
+        connection: *mut raw::c_void,
+        xwin: u32,
+        visual_id: u32,
+    ) -> Result<crate::RawContext<NotCurrent>, CreationError>
+    where
+        Self: Sized,
+    {
+        self.build_with(|pf_reqs, gl_attr| {
+            Context::new_raw_xcb_context(connection, xwin, visual_id, pf_reqs, gl_attr)
+                .map(|context| ((), context))
+        })
+        .map(|(window, context)| crate::RawContext { context, window })
+    }
+
+    #[inline]
+    #[cfg(feature = "x11")]
+    fn choose_x11_visual(&self, xconn: &Arc<XConnection>) -> Result<X11Visual, CreationError> {
+        self.query_with(|pf_reqs, gl_attr| {
+            Context::is_compatible(&gl_attr.sharing, ContextType::X11)?;
+            let gl_attr = gl_attr.clone().map_sharing(|ctx| match *ctx {
+                Context::X11(ref ctx) => ctx,
+                _ => unreachable!(),
+            });
+            x11::Context::choose_visual(xconn, pf_reqs, &gl_attr)
+        })
+    }
+}
+
+/// The requirements on the X screen and visual of a context, as set with
+/// [`ContextBuilderExtUnix`].
+///
+/// [`ContextBuilderExtUnix`]: trait.ContextBuilderExtUnix.html
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct X11Requirements {
+    /// The id the visual must have. Set internally to the visual of the window
+    /// a raw context is built for.
+    pub visual_xid: Option<raw::c_ulong>,
+    /// The depth, in bits, the visual must have.
+    pub visual_depth: Option<u8>,
+    /// The class the visual must have, `None` requesting a `TrueColor`
+    /// visual.
+    pub visual_class: Option<raw::c_int>,
+    /// The screen to create the context on, `None` using the default screen
+    /// of the display.
+    pub screen: Option<raw::c_int>,
+}
+
+/// A unix-specific extension to the [`ContextBuilder`] which allows choosing
+/// the X screen and visual of the context.
+///
+/// These are ignored when not using X11, and by
+/// [`RawContextExt::build_raw_x11_context`], which uses the screen and visual
+/// of the window it is given.
+///
+/// [`ContextBuilder`]: ../../struct.ContextBuilder.html
+/// [`RawContextExt::build_raw_x11_context`]:
+/// trait.RawContextExt.html#tymethod.build_raw_x11_context
+pub trait ContextBuilderExtUnix {
+    /// Creates the context, and its window if any, on the given X screen
+    /// instead of the default screen of the display.
+    fn with_x11_screen(self, screen_id: raw::c_int) -> Self;
+
+    /// Requires the visual of the context to have the given id.
+    fn with_x11_visual_id(self, visual_id: raw::c_ulong) -> Self;
+
+    /// Requires the visual of the context to have the given depth, e.g. `32`
+    /// for a visual with an alpha channel.
+    fn with_x11_visual_depth(self, depth: u8) -> Self;
+
+    /// Requires the visual of the context to have the given class, e.g.
+    /// `x11::ffi::DirectColor`. By default a `TrueColor` visual is chosen.
+    fn with_x11_visual_class(self, class: raw::c_int) -> Self;
+}
+
+impl<'a, T: ContextCurrentState> ContextBuilderExtUnix for crate::ContextBuilder<'a, T> {
+    #[inline]
+    fn with_x11_screen(mut self, screen_id: raw::c_int) -> Self {
+        self.pf_reqs.x11.screen = Some(screen_id);
+        self
+    }
+
+    #[inline]
+    fn with_x11_visual_id(mut self, visual_id: raw::c_ulong) -> Self {
+        self.pf_reqs.x11.visual_xid = Some(visual_id);
+        self
+    }
+
+    #[inline]
+    fn with_x11_visual_depth(mut self, depth: u8) -> Self {
+        self.pf_reqs.x11.visual_depth = Some(depth);
+        self
+    }
+
+    #[inline]
+    fn with_x11_visual_class(mut self, class: raw::c_int) -> Self {
+        self.pf_reqs.x11.visual_class = Some(class);
+        self
+    }
+}
+
+/// A unix-specific extension to the [`ContextWrapper`] for HiDPI Wayland
+/// surfaces.
+///
+/// [`ContextWrapper`]: ../../struct.ContextWrapper.html
+pub trait ContextWrapperExtUnix {
+    /// Like [`resize`], but also sets the buffer scale of the Wayland surface
+    /// to `scale_factor`, and attaches the next buffer at `offset` relative to
+    /// the current one, e.g. to grow the surface from its top-left corner.
+    ///
+    /// `size` is in physical pixels, and rounded down to a multiple of
+    /// `scale_factor` as Wayland requires. Winit windows have to be given the
+    /// scale factor of the window, which is always integral on Wayland.
+    ///
+    /// Other surfaces are only resized.
+    ///
+    /// [`resize`]: ../../struct.ContextWrapper.html#method.resize
+    fn resize_with_scale(
+        &self,
+        size: dpi::PhysicalSize<u32>,
+        scale_factor: i32,
+        offset: dpi::LogicalPosition<i32>,
+    );
+}
+
+impl<W> ContextWrapperExtUnix for crate::ContextWrapper<PossiblyCurrent, W> {
+    #[inline]
+    fn resize_with_scale(
+        &self,
+        size: dpi::PhysicalSize<u32>,
+        scale_factor: i32,
+        offset: dpi::LogicalPosition<i32>,
+    ) {
+        self.context.context.resize_with_scale(
+            size.width,
+            size.height,
+            scale_factor,
+            offset.x,
+            offset.y,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offscreen_backends_parse() {
+        use OffscreenBackend::*;
+
+        assert_eq!(OffscreenBackend::parse("osmesa").unwrap(), Some(vec![OsMesa]));
+        assert_eq!(
+            OffscreenBackend::parse("pbuffer,surfaceless").unwrap(),
+            Some(vec![PBuffer, Surfaceless])
+        );
+    }
+
+    #[test]
+    fn offscreen_backends_whitespace() {
+        use OffscreenBackend::*;
+
+        assert_eq!(
+            OffscreenBackend::parse(" surfaceless , osmesa\t").unwrap(),
+            Some(vec![Surfaceless, OsMesa])
+        );
+        assert_eq!(OffscreenBackend::parse("pbuffer, ,").unwrap(), Some(vec![PBuffer]));
+    }
+
+    #[test]
+    fn offscreen_backends_duplicates() {
+        use OffscreenBackend::*;
+
+        assert_eq!(
+            OffscreenBackend::parse("osmesa,pbuffer,osmesa").unwrap(),
+            Some(vec![OsMesa, PBuffer])
+        );
+    }
+
+    #[test]
+    fn offscreen_backends_empty() {
+        assert_eq!(OffscreenBackend::parse("").unwrap(), None);
+        assert_eq!(OffscreenBackend::parse(" , ").unwrap(), None);
+    }
+
+    #[test]
+    fn offscreen_backends_unknown() {
+        match OffscreenBackend::parse("surfaceless,egl") {
+            Err(CreationError::NotSupported(msg)) => assert!(msg.contains("`egl`")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(OffscreenBackend::parse("OSMesa").is_err());
+    }
+
+    #[test]
+    fn offscreen_failures_name_backends() {
+        let failures = OffscreenFailures(vec![
+            (OffscreenBackend::Surfaceless, CreationError::NotSupported("no EGL".to_string())),
+            (OffscreenBackend::OsMesa, CreationError::NoAvailablePixelFormat),
+        ]);
+        assert_eq!(
+            failures.to_string(),
+            "Every offscreen backend failed: Surfaceless: no EGL; OsMesa: Couldn't find any \
+             pixel format that matches the criteria."
+        );
     }
 }
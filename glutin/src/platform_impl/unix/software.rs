@@ -0,0 +1,600 @@
+#![cfg(feature = "winit")]
+
+//! OSMesa contexts showing what they render in a window, for machines
+//! without a working GL driver.
+
+use crate::api::osmesa::OsMesaContext;
+use crate::{
+    Api, ContextError, CreationError, GlAttributes, PixelFormat, PixelFormatRequirements, Rect,
+    RendererInfo,
+};
+
+#[cfg(feature = "x11")]
+use crate::platform::unix::x11::{ffi as xlib, XConnection};
+use crate::platform::unix::{EventLoopWindowTargetExtUnix, WindowExtUnix};
+use parking_lot::Mutex;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::window::{Window, WindowBuilder};
+
+use std::os::raw;
+#[cfg(feature = "x11")]
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Context {
+    osmesa: OsMesaContext,
+    presenter: Mutex<Presenter>,
+    pixel_format: PixelFormat,
+}
+
+#[derive(Debug)]
+enum Presenter {
+    #[cfg(feature = "x11")]
+    X11(X11Presenter),
+    #[cfg(feature = "wayland")]
+    Wayland(wayland::WaylandPresenter),
+}
+
+impl Context {
+    pub fn new<T>(
+        wb: WindowBuilder,
+        el: &EventLoopWindowTarget<T>,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&super::Context>,
+    ) -> Result<(Window, Self), CreationError> {
+        if gl_attr.sharing.is_some() {
+            return Err(CreationError::NotSupported(
+                "context sharing not possible with OSMesa".to_string(),
+            ));
+        }
+        let gl_attr = gl_attr.clone().map_sharing(|_| unreachable!());
+
+        let transparent = wb.window.transparent;
+        let win = wb.build(el)?;
+        let osmesa = OsMesaContext::new_windowed(pf_reqs, &gl_attr, win.inner_size())?;
+
+        let presenter = Self::new_presenter(el, &win, transparent)?;
+        let transparency = presenter.transparent();
+
+        let pixel_format = PixelFormat {
+            hardware_accelerated: false,
+            color_bits: 24,
+            alpha_bits: 8,
+            depth_bits: pf_reqs.depth_bits.unwrap_or(0),
+            stencil_bits: pf_reqs.stencil_bits.unwrap_or(0),
+            stereoscopy: false,
+            double_buffer: true,
+            multisampling: None,
+            srgb: false,
+            transparency,
+        };
+
+        Ok((win, Context { osmesa, presenter: Mutex::new(presenter), pixel_format }))
+    }
+
+    #[cfg_attr(not(feature = "wayland"), allow(unused_variables))]
+    fn new_presenter<T>(
+        el: &EventLoopWindowTarget<T>,
+        win: &Window,
+        transparent: bool,
+    ) -> Result<Presenter, CreationError> {
+        #[cfg(feature = "wayland")]
+        if el.is_wayland() {
+            let display = win.wayland_display().unwrap();
+            let surface = win.wayland_surface().unwrap();
+            return unsafe { wayland::WaylandPresenter::new(display, surface, transparent) }
+                .map(Presenter::Wayland);
+        }
+        #[cfg(feature = "x11")]
+        if el.is_x11() {
+            let xconn = el.xlib_xconnection().unwrap();
+            let xwin = win.xlib_window().unwrap();
+            return X11Presenter::new(xconn, xwin).map(Presenter::X11);
+        }
+        panic!("glutin was not compiled with support for this display server")
+    }
+
+    #[inline]
+    pub unsafe fn make_current(&self) -> Result<(), ContextError> {
+        self.osmesa.make_current()
+    }
+
+    #[inline]
+    pub unsafe fn make_not_current(&self) -> Result<(), ContextError> {
+        self.osmesa.make_not_current()
+    }
+
+    #[inline]
+    pub fn is_current(&self) -> bool {
+        self.osmesa.is_current()
+    }
+
+    #[inline]
+    pub fn get_api(&self) -> Api {
+        self.osmesa.get_api()
+    }
+
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        self.osmesa.get_api_version()
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        self.osmesa.renderer_info()
+    }
+
+    #[inline]
+    pub unsafe fn raw_handle(&self) -> *mut raw::c_void {
+        self.osmesa.raw_handle()
+    }
+
+    #[inline]
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        // minimized windows may be resized to nothing, and the buffer is kept
+        // until they're shown again
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.osmesa.resize(width, height)
+    }
+
+    #[inline]
+    pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
+        self.osmesa.get_proc_address(addr)
+    }
+
+    #[inline]
+    pub fn swap_buffers(&self) -> Result<(), ContextError> {
+        self.present(None)
+    }
+
+    #[inline]
+    pub fn swap_buffers_with_damage(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        self.present(Some(rects))
+    }
+
+    #[inline]
+    pub fn get_pixel_format(&self) -> PixelFormat {
+        self.pixel_format.clone()
+    }
+
+    /// Shows the buffer in the window, only updating `damage` if given.
+    fn present(&self, damage: Option<&[Rect]>) -> Result<(), ContextError> {
+        // the buffer is only complete once rendering has finished
+        if self.is_current() {
+            let gl_finish_fn = self.get_proc_address("glFinish");
+            if !gl_finish_fn.is_null() {
+                let gl_finish_fn = unsafe {
+                    std::mem::transmute::<*const core::ffi::c_void, extern "system" fn()>(
+                        gl_finish_fn,
+                    )
+                };
+                gl_finish_fn();
+            }
+        }
+
+        let mut presenter = self.presenter.lock();
+        self.osmesa.with_buffer(|pixels, width, height| {
+            if width == 0 || height == 0 {
+                return Ok(());
+            }
+            match *presenter {
+                #[cfg(feature = "x11")]
+                Presenter::X11(ref mut presenter) => {
+                    presenter.present(pixels, width, height, damage)
+                }
+                #[cfg(feature = "wayland")]
+                Presenter::Wayland(ref mut presenter) => {
+                    presenter.present(pixels, width, height, damage)
+                }
+            }
+        })
+    }
+}
+
+impl Presenter {
+    /// Whether the alpha of the buffer is used by the window system.
+    fn transparent(&self) -> bool {
+        match *self {
+            #[cfg(feature = "x11")]
+            Presenter::X11(ref presenter) => presenter.depth == 32,
+            #[cfg(feature = "wayland")]
+            Presenter::Wayland(ref presenter) => presenter.transparent(),
+        }
+    }
+}
+
+/// Converts a rectangle counted from the bottom-left corner of the buffer to
+/// `(x, y, width, height)` counted from its top-left corner, clipped to the
+/// buffer.
+fn to_top_left(rect: &Rect, width: u32, height: u32) -> (u32, u32, u32, u32) {
+    let x = rect.x.min(width);
+    let bottom = rect.y.min(height);
+    let top = rect.y.saturating_add(rect.height).min(height);
+    (x, height - top, x.saturating_add(rect.width).min(width) - x, top - bottom)
+}
+
+/// Puts the buffer in the window with `XPutImage`.
+#[cfg(feature = "x11")]
+#[derive(Debug)]
+struct X11Presenter {
+    xconn: Arc<XConnection>,
+    xwin: raw::c_ulong,
+    gc: xlib::GC,
+    visual: *mut xlib::Visual,
+    depth: raw::c_int,
+}
+
+// The GC and visual belong to the display, which is thread-safe.
+#[cfg(feature = "x11")]
+unsafe impl Send for X11Presenter {}
+
+#[cfg(feature = "x11")]
+impl X11Presenter {
+    fn new(xconn: Arc<XConnection>, xwin: raw::c_ulong) -> Result<Self, CreationError> {
+        let mut attrs = unsafe { std::mem::zeroed::<xlib::XWindowAttributes>() };
+        unsafe { (xconn.xlib.XGetWindowAttributes)(xconn.display, xwin, &mut attrs) };
+        xconn.check_errors().map_err(|err| {
+            CreationError::OsError(format!("`XGetWindowAttributes` failed: {:?}", err))
+        })?;
+
+        // the buffer holds 32-bit BGRA pixels, which only a direct-color
+        // visual with 8 bits per channel can show as is
+        let visual = unsafe { &*attrs.visual };
+        if (attrs.depth != 24 && attrs.depth != 32)
+            || visual.red_mask != 0xff0000
+            || visual.green_mask != 0xff00
+            || visual.blue_mask != 0xff
+        {
+            return Err(CreationError::NotSupported(format!(
+                "the visual of the window (depth {}) can't show BGRA pixels",
+                attrs.depth
+            )));
+        }
+
+        let gc = unsafe { (xconn.xlib.XCreateGC)(xconn.display, xwin, 0, std::ptr::null_mut()) };
+        if gc.is_null() {
+            return Err(CreationError::OsError("`XCreateGC` failed".to_string()));
+        }
+
+        Ok(X11Presenter { xconn, xwin, gc, visual: attrs.visual, depth: attrs.depth })
+    }
+
+    fn present(
+        &mut self,
+        pixels: &[u32],
+        width: u32,
+        height: u32,
+        damage: Option<&[Rect]>,
+    ) -> Result<(), ContextError> {
+        let xlib = &self.xconn.xlib;
+        let display = self.xconn.display;
+        let full = [Rect { x: 0, y: 0, width, height }];
+        unsafe {
+            let image = (xlib.XCreateImage)(
+                display,
+                self.visual,
+                self.depth as raw::c_uint,
+                xlib::ZPixmap,
+                0,
+                pixels.as_ptr() as *mut raw::c_char,
+                width,
+                height,
+                32,
+                (width * 4) as raw::c_int,
+            );
+            if image.is_null() {
+                return Err(ContextError::OsError("`XCreateImage` failed".to_string()));
+            }
+            // the bytes of each pixel are B, G, R and A, whatever the byte
+            // order of the server; Xlib swaps them if needed
+            (*image).byte_order = xlib::LSBFirst;
+
+            for rect in damage.unwrap_or(&full) {
+                let (x, y, w, h) = to_top_left(rect, width, height);
+                if w > 0 && h > 0 {
+                    let (x, y) = (x as raw::c_int, y as raw::c_int);
+                    (xlib.XPutImage)(display, self.xwin, self.gc, image, x, y, x, y, w, h);
+                }
+            }
+
+            // the pixels belong to the OSMesa context
+            (*image).data = std::ptr::null_mut();
+            (xlib.XDestroyImage)(image);
+            (xlib.XFlush)(display);
+        }
+
+        self.xconn
+            .check_errors()
+            .map_err(|err| ContextError::OsError(format!("`XPutImage` failed: {:?}", err)))
+    }
+}
+
+#[cfg(feature = "x11")]
+impl Drop for X11Presenter {
+    fn drop(&mut self) {
+        unsafe { (self.xconn.xlib.XFreeGC)(self.xconn.display, self.gc) };
+    }
+}
+
+/// Attaches `wl_shm` buffers holding copies of the buffer to the surface.
+#[cfg(feature = "wayland")]
+mod wayland {
+    use super::to_top_left;
+    use crate::{ContextError, CreationError, Rect};
+
+    use wayland_client::protocol::wl_buffer::{self, WlBuffer};
+    use wayland_client::protocol::wl_shm::{self, WlShm};
+    use wayland_client::protocol::wl_shm_pool::WlShmPool;
+    use wayland_client::protocol::wl_surface::WlSurface;
+    use wayland_client::sys::client::wl_display;
+    use wayland_client::{Display, EventQueue, GlobalManager, Main, Proxy};
+
+    use std::fs::{File, OpenOptions};
+    use std::os::raw;
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::io::AsRawFd;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // the compositor may hold on to a buffer for a while after the next one
+    // is attached, so up to this many are used in turn
+    const MAX_BUFFERS: usize = 3;
+
+    pub struct WaylandPresenter {
+        display: Display,
+        queue: EventQueue,
+        surface: WlSurface,
+        format: wl_shm::Format,
+        // the memory shared with the compositor, holding every buffer
+        file: File,
+        pool: Main<WlShmPool>,
+        pool_size: usize,
+        buffers: Vec<ShmBuffer>,
+        buffer_size: (u32, u32),
+        // buffers of a previous size, destroyed once the compositor releases
+        // them, whose memory isn't reused until then
+        retired: Vec<ShmBuffer>,
+    }
+
+    struct ShmBuffer {
+        buffer: Main<WlBuffer>,
+        offset: usize,
+        len: usize,
+        released: Arc<AtomicBool>,
+    }
+
+    impl std::fmt::Debug for WaylandPresenter {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "WaylandPresenter(...)")
+        }
+    }
+
+    // The queue, and the proxies dispatched by it, are only used through the
+    // mutex of the context, so by one thread at a time.
+    unsafe impl Send for WaylandPresenter {}
+
+    impl WaylandPresenter {
+        pub unsafe fn new(
+            display: *mut raw::c_void,
+            surface: *mut raw::c_void,
+            transparent: bool,
+        ) -> Result<Self, CreationError> {
+            let display = Display::from_external_display(display as *mut wl_display);
+            let mut queue = display.create_event_queue();
+            let attached = (*display).clone().attach(queue.token());
+            let globals = GlobalManager::new(&attached);
+            queue
+                .sync_roundtrip(&mut (), |_, _, _| {})
+                .map_err(|err| CreationError::OsError(format!("roundtrip failed: {}", err)))?;
+            let shm = globals
+                .instantiate_exact::<WlShm>(1)
+                .map_err(|err| CreationError::OsError(format!("no `wl_shm`: {:?}", err)))?;
+
+            let surface: Proxy<WlSurface> = Proxy::from_c_ptr(surface as *mut _);
+            let file = create_shm_file()
+                .map_err(|err| CreationError::OsError(format!("no shared memory: {}", err)))?;
+            // the pool can't be empty
+            file.set_len(1)
+                .map_err(|err| CreationError::OsError(format!("no shared memory: {}", err)))?;
+            let pool = shm.create_pool(file.as_raw_fd(), 1);
+
+            Ok(WaylandPresenter {
+                display,
+                queue,
+                surface: surface.into(),
+                // ARGB8888 and XRGB8888 are little-endian, i.e. BGRA in memory
+                format: if transparent {
+                    wl_shm::Format::Argb8888
+                } else {
+                    wl_shm::Format::Xrgb8888
+                },
+                file,
+                pool,
+                pool_size: 1,
+                buffers: Vec::new(),
+                buffer_size: (0, 0),
+                retired: Vec::new(),
+            })
+        }
+
+        pub fn transparent(&self) -> bool {
+            self.format == wl_shm::Format::Argb8888
+        }
+
+        pub fn present(
+            &mut self,
+            pixels: &[u32],
+            width: u32,
+            height: u32,
+            damage: Option<&[Rect]>,
+        ) -> Result<(), ContextError> {
+            if self.buffer_size != (width, height) {
+                self.retired.append(&mut self.buffers);
+                self.buffer_size = (width, height);
+            }
+
+            self.queue.dispatch_pending(&mut (), |_, _, _| {}).map_err(ContextError::IoError)?;
+            self.retired.retain(|buffer| {
+                let released = buffer.released.load(Ordering::Acquire);
+                if released {
+                    buffer.buffer.destroy();
+                }
+                !released
+            });
+            let index = loop {
+                if let Some(index) =
+                    self.buffers.iter().position(|buffer| buffer.released.load(Ordering::Acquire))
+                {
+                    break index;
+                }
+                if self.buffers.len() < MAX_BUFFERS {
+                    break self.add_buffer()?;
+                }
+                // wait for the compositor to release one
+                self.queue.dispatch(&mut (), |_, _, _| {}).map_err(ContextError::IoError)?;
+            };
+
+            let buffer = &self.buffers[index];
+            let bytes = unsafe {
+                std::slice::from_raw_parts(pixels.as_ptr() as *const u8, pixels.len() * 4)
+            };
+            self.file.write_all_at(bytes, buffer.offset as u64).map_err(ContextError::IoError)?;
+            buffer.released.store(false, Ordering::Release);
+
+            self.surface.attach(Some(&buffer.buffer), 0, 0);
+            let full = [Rect { x: 0, y: 0, width, height }];
+            for rect in damage.unwrap_or(&full) {
+                let (x, y, w, h) = to_top_left(rect, width, height);
+                // `damage_buffer` was added in version 4 of `wl_surface`
+                if self.surface.as_ref().version() >= 4 {
+                    self.surface.damage_buffer(x as i32, y as i32, w as i32, h as i32);
+                } else {
+                    self.surface.damage(x as i32, y as i32, w as i32, h as i32);
+                }
+            }
+            self.surface.commit();
+            self.display.flush().map_err(ContextError::IoError)
+        }
+
+        fn add_buffer(&mut self) -> Result<usize, ContextError> {
+            let (width, height) = self.buffer_size;
+            let stride = width as usize * 4;
+            let len = stride * height as usize;
+            // the compositor may still read the buffers, retired or not
+            let used = self.buffers.iter().chain(&self.retired).map(|b| (b.offset, b.len));
+            let offset = free_offset(used.collect(), len);
+            let end = offset + len;
+            if end > self.pool_size {
+                // pools can only grow
+                self.file.set_len(end as u64).map_err(ContextError::IoError)?;
+                self.pool.resize(end as i32);
+                self.pool_size = end;
+            }
+
+            let buffer = self.pool.create_buffer(
+                offset as i32,
+                width as i32,
+                height as i32,
+                stride as i32,
+                self.format,
+            );
+            let released = Arc::new(AtomicBool::new(true));
+            let released_clone = Arc::clone(&released);
+            buffer.quick_assign(move |_, event, _| {
+                if let wl_buffer::Event::Release = event {
+                    released_clone.store(true, Ordering::Release);
+                }
+            });
+
+            self.buffers.push(ShmBuffer { buffer, offset, len, released });
+            Ok(self.buffers.len() - 1)
+        }
+    }
+
+    impl Drop for WaylandPresenter {
+        fn drop(&mut self) {
+            for buffer in self.buffers.drain(..).chain(self.retired.drain(..)) {
+                buffer.buffer.destroy();
+            }
+            self.pool.destroy();
+            let _ = self.display.flush();
+        }
+    }
+
+    /// Returns the lowest offset of the pool where `len` bytes overlap none of
+    /// the `used` ranges, given as offsets and lengths.
+    fn free_offset(mut used: Vec<(usize, usize)>, len: usize) -> usize {
+        used.sort_unstable();
+        let mut offset = 0;
+        for (start, used_len) in used {
+            if offset + len <= start {
+                break;
+            }
+            offset = offset.max(start + used_len);
+        }
+        offset
+    }
+
+    /// Creates an unlinked file in `XDG_RUNTIME_DIR`, which is usually a
+    /// tmpfs, to share with the compositor.
+    fn create_shm_file() -> std::io::Result<File> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let path = dir.join(format!(
+            "glutin-shm-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        std::fs::remove_file(&path)?;
+        Ok(file)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn free_offset_in_gaps() {
+            assert_eq!(free_offset(vec![], 16), 0);
+            assert_eq!(free_offset(vec![(0, 16), (16, 16)], 16), 32);
+            assert_eq!(free_offset(vec![(32, 16), (0, 16)], 16), 16);
+            // the gap is too small
+            assert_eq!(free_offset(vec![(0, 16), (24, 16)], 16), 40);
+            assert_eq!(free_offset(vec![(8, 16)], 8), 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_top_left_flips() {
+        let rect = Rect { x: 10, y: 20, width: 30, height: 40 };
+        assert_eq!(to_top_left(&rect, 100, 100), (10, 40, 30, 40));
+        let full = Rect { x: 0, y: 0, width: 100, height: 50 };
+        assert_eq!(to_top_left(&full, 100, 50), (0, 0, 100, 50));
+    }
+
+    #[test]
+    fn to_top_left_clips() {
+        // past the top-right corner
+        let rect = Rect { x: 80, y: 30, width: 40, height: 40 };
+        assert_eq!(to_top_left(&rect, 100, 50), (80, 0, 20, 20));
+        // out of the buffer
+        let rect = Rect { x: 120, y: 60, width: 10, height: 10 };
+        assert_eq!(to_top_left(&rect, 100, 50), (100, 0, 0, 0));
+        // sizes that would overflow
+        let rect = Rect { x: 90, y: 40, width: u32::MAX, height: u32::MAX };
+        assert_eq!(to_top_left(&rect, 100, 50), (90, 0, 10, 10));
+        let rect = Rect { x: u32::MAX, y: u32::MAX, width: u32::MAX, height: u32::MAX };
+        assert_eq!(to_top_left(&rect, 100, 50), (100, 0, 0, 0));
+    }
+}
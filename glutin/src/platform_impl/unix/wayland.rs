@@ -1,23 +1,36 @@
 #![cfg(feature = "wayland")]
 
-use crate::api::egl::{Context as EglContext, NativeDisplay, SurfaceType as EglSurfaceType};
+use crate::api::egl::{
+    self, Context as EglContext, EglDisplay, NativeDisplay, SurfaceType as EglSurfaceType,
+};
+use crate::dpi;
 use crate::{
-    ContextError, CreationError, GlAttributes, PixelFormat, PixelFormatRequirements, Rect,
+    ContextError, ContextPriority, CreationError, GlAttributes, PixelFormat,
+    PixelFormatRequirements, Rect, RendererInfo,
 };
 
-use crate::platform::unix::{EventLoopWindowTargetExtUnix, WindowExtUnix};
+#[cfg(feature = "winit")]
+use crate::platform::unix::WindowExtUnix;
 use glutin_egl_sys as ffi;
+use wayland_client::protocol::wl_surface::WlSurface;
 pub use wayland_client::sys::client::wl_display;
-use winit;
-use winit::dpi;
+use wayland_client::Proxy;
+#[cfg(feature = "winit")]
 use winit::event_loop::EventLoopWindowTarget;
+#[cfg(feature = "winit")]
 use winit::window::{Window, WindowBuilder};
 
 use std::ops::Deref;
 use std::os::raw;
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
 
-pub struct EglSurface(Arc<wayland_egl::WlEglSurface>);
+pub struct EglSurface {
+    egl_surface: Arc<wayland_egl::WlEglSurface>,
+    // the `wl_surface` the EGL surface was created for
+    surface: *mut raw::c_void,
+    buffer_scale: AtomicI32,
+}
 
 impl std::fmt::Debug for EglSurface {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -25,6 +38,37 @@ impl std::fmt::Debug for EglSurface {
     }
 }
 
+// libwayland proxies can be used from any thread.
+unsafe impl Send for EglSurface {}
+unsafe impl Sync for EglSurface {}
+
+impl EglSurface {
+    fn resize(&self, width: u32, height: u32, scale: i32, dx: i32, dy: i32) {
+        let scale = scale.max(1);
+        let (width, height) = scaled_size(width, height, scale);
+
+        // the scale applies with the next commit, along with the resized
+        // buffer attached by `eglSwapBuffers`
+        if self.buffer_scale.swap(scale, Ordering::Relaxed) != scale {
+            let surface: Proxy<WlSurface> = unsafe { Proxy::from_c_ptr(self.surface as *mut _) };
+            // `set_buffer_scale` was added in version 3 of `wl_surface`
+            if surface.version() >= 3 {
+                WlSurface::from(surface).set_buffer_scale(scale);
+            }
+        }
+
+        self.egl_surface.resize(width, height, dx, dy);
+    }
+}
+
+/// Rounds `width` and `height` down to a multiple of `scale`, as the
+/// compositor may reject buffers whose size isn't a multiple of their scale.
+fn scaled_size(width: u32, height: u32, scale: i32) -> (i32, i32) {
+    let width = (width.min(i32::MAX as u32) as i32 / scale).max(1) * scale;
+    let height = (height.min(i32::MAX as u32) as i32 / scale).max(1) * scale;
+    (width, height)
+}
+
 #[derive(Debug)]
 pub enum Context {
     Windowed(EglContext, EglSurface),
@@ -46,14 +90,13 @@ impl Deref for Context {
 
 impl Context {
     #[inline]
-    pub fn new_headless<T>(
-        el: &EventLoopWindowTarget<T>,
+    pub fn new_headless(
+        display_ptr: *const wl_display,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
         size: Option<dpi::PhysicalSize<u32>>,
     ) -> Result<Self, CreationError> {
         let gl_attr = gl_attr.clone().map_sharing(|c| &**c);
-        let display_ptr = el.wayland_display().unwrap() as *const _;
         let native_display = NativeDisplay::Wayland(Some(display_ptr as *const _));
         if let Some(size) = size {
             let context = EglContext::new(
@@ -82,12 +125,14 @@ impl Context {
     }
 
     #[inline]
+    #[cfg(feature = "winit")]
     pub fn new<T>(
         wb: WindowBuilder,
         el: &EventLoopWindowTarget<T>,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
     ) -> Result<(Window, Self), CreationError> {
+        let transparent = wb.window.transparent;
         let win = wb.build(el)?;
 
         let size = win.inner_size();
@@ -102,7 +147,15 @@ impl Context {
             }
         };
 
-        let context = Self::new_raw_context(display_ptr, surface, width, height, pf_reqs, gl_attr)?;
+        let context = Self::new_raw_context(
+            display_ptr,
+            surface,
+            width,
+            height,
+            transparent,
+            pf_reqs,
+            gl_attr,
+        )?;
         Ok((win, context))
     }
 
@@ -112,6 +165,7 @@ impl Context {
         surface: *mut raw::c_void,
         width: u32,
         height: u32,
+        transparent: bool,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
     ) -> Result<Self, CreationError> {
@@ -121,12 +175,32 @@ impl Context {
         let context = {
             let gl_attr = gl_attr.clone().map_sharing(|c| &**c);
             let native_display = NativeDisplay::Wayland(Some(display_ptr as *const _));
-            EglContext::new(pf_reqs, &gl_attr, native_display, EglSurfaceType::Window, |c, _| {
+            // prefer a config with an alpha channel for transparent windows
+            let select_config = |c: Vec<_>, display| {
+                if transparent {
+                    for &config in &c {
+                        if egl::get_alpha_size(display, config)? > 0 {
+                            return Ok(config);
+                        }
+                    }
+                }
                 Ok(c[0])
-            })
+            };
+            EglContext::new(
+                pf_reqs,
+                &gl_attr,
+                native_display,
+                EglSurfaceType::Window,
+                select_config,
+            )
             .and_then(|p| p.finish(egl_surface.ptr() as *const _))?
         };
-        let context = Context::Windowed(context, EglSurface(Arc::new(egl_surface)));
+        let egl_surface = EglSurface {
+            egl_surface: Arc::new(egl_surface),
+            surface,
+            buffer_scale: AtomicI32::new(1),
+        };
+        let context = Context::Windowed(context, egl_surface);
         Ok(context)
     }
 
@@ -150,6 +224,11 @@ impl Context {
         (**self).get_api()
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        (**self).get_api_version()
+    }
+
     #[inline]
     pub unsafe fn raw_handle(&self) -> ffi::EGLContext {
         (**self).raw_handle()
@@ -161,10 +240,36 @@ impl Context {
     }
 
     #[inline]
-    pub fn resize(&self, width: u32, height: u32) {
+    pub fn egl_display(&self) -> Option<EglDisplay> {
+        Some((**self).egl_display().clone())
+    }
+
+    #[inline]
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        match self {
+            Context::Windowed(_, surface) => {
+                // keep the buffers a multiple of the scale set by
+                // `resize_with_scale`
+                surface.resize(width, height, surface.buffer_scale.load(Ordering::Relaxed), 0, 0);
+                Ok(())
+            }
+            Context::PBuffer(ctx) => ctx.resize_pbuffer(width, height),
+            Context::Surfaceless(_) => Ok(()),
+        }
+    }
+
+    /// Resizes the buffers to `width` and `height` physical pixels, rounded
+    /// down to a multiple of `scale`, and sets the buffer scale of the surface
+    /// to `scale`. The next buffer is attached at `dx` and `dy`, in
+    /// surface-local coordinates, relative to the current one.
+    #[inline]
+    pub fn resize_with_scale(&self, width: u32, height: u32, scale: i32, dx: i32, dy: i32) {
         match self {
-            Context::Windowed(_, surface) => surface.0.resize(width as i32, height as i32, 0, 0),
-            _ => unreachable!(),
+            Context::Windowed(_, surface) => surface.resize(width, height, scale, dx, dy),
+            // other contexts aren't windowed, and can't be resized this way
+            _ => {
+                let _ = self.resize(width, height);
+            }
         }
     }
 
@@ -188,8 +293,56 @@ impl Context {
         (**self).swap_buffers_with_damage_supported()
     }
 
+    #[inline]
+    pub fn set_damage_region(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        (**self).set_damage_region(rects)
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
+        (**self).set_damage_region_supported()
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        (**self).buffer_age()
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        (**self).platform_extensions()
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        (**self).priority()
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        (**self).renderer_info()
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         (**self).get_pixel_format().clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_size_rounds_down() {
+        assert_eq!(scaled_size(800, 600, 1), (800, 600));
+        assert_eq!(scaled_size(801, 601, 2), (800, 600));
+        assert_eq!(scaled_size(800, 600, 3), (798, 600));
+    }
+
+    #[test]
+    fn scaled_size_keeps_one_scale() {
+        assert_eq!(scaled_size(1, 0, 2), (2, 2));
+        assert_eq!(scaled_size(0, 0, 1), (1, 1));
+    }
+}
@@ -1,21 +1,23 @@
 #![cfg(feature = "x11")]
 
 use crate::api::egl::{
-    self, Context as EglContext, NativeDisplay, SurfaceType as EglSurfaceType, EGL,
+    self, Context as EglContext, EglDisplay, NativeDisplay, SurfaceType as EglSurfaceType, EGL,
 };
 use crate::api::glx::{Context as GlxContext, GLX};
+use crate::dpi;
 use crate::platform::unix::x11::XConnection;
+#[cfg(feature = "winit")]
 use crate::platform::unix::{EventLoopWindowTargetExtUnix, WindowBuilderExtUnix, WindowExtUnix};
-use crate::platform_impl::x11_utils;
+use crate::platform_impl::{x11_utils, X11Visual};
 use crate::{
-    Api, ContextError, CreationError, GlAttributes, GlRequest, PixelFormat,
-    PixelFormatRequirements, Rect,
+    Api, ContextError, ContextPriority, CreationError, GlAttributes, GlRequest, PixelFormat,
+    PixelFormatRequirements, Rect, RendererInfo,
 };
 
 use glutin_glx_sys as ffi;
-use winit;
-use winit::dpi;
+#[cfg(feature = "winit")]
 use winit::event_loop::EventLoopWindowTarget;
+#[cfg(feature = "winit")]
 use winit::window::{Window, WindowBuilder};
 
 use std::ops::{Deref, DerefMut};
@@ -23,12 +25,17 @@ use std::os::raw;
 use std::sync::Arc;
 
 pub mod utils;
+#[cfg(not(feature = "winit"))]
+pub mod xconnection;
 
+#[cfg(feature = "winit")]
 #[derive(Debug)]
 struct NoX11Connection;
 
+#[cfg(feature = "winit")]
 impl std::error::Error for NoX11Connection {}
 
+#[cfg(feature = "winit")]
 impl std::fmt::Display for NoX11Connection {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.write_str("failed to get x11 connection")
@@ -43,7 +50,8 @@ pub enum X11Context {
 
 #[derive(Debug)]
 pub struct ContextInner {
-    xconn: Arc<XConnection>,
+    // `None` for contexts created from an XCB connection with EGL
+    xconn: Option<Arc<XConnection>>,
     context: X11Context,
 }
 
@@ -84,12 +92,10 @@ impl DerefMut for Context {
 unsafe impl Send for Context {}
 unsafe impl Sync for Context {}
 
-// FIXME:
-// When using egl, all the configs will not support transparency, even if
-// transparency does work with glx.
+// With EGL, older Mesa drivers don't expose any config whose visual has an
+// alpha mask, so transparency only works with GLX there.
 //
-// https://bugs.freedesktop.org/show_bug.cgi?id=67676<Paste>
-// I'm on a patch.
+// https://bugs.freedesktop.org/show_bug.cgi?id=67676
 pub fn select_config<T, F>(
     xconn: &Arc<XConnection>,
     transparent: Option<bool>,
@@ -114,7 +120,7 @@ where
             &xconn,
             visual_infos,
             transparent == Some(true),
-            pf_reqs.x11_visual_xid,
+            Some(pf_reqs),
         );
 
         match (lacks_what, &this_lacks_what) {
@@ -135,10 +141,10 @@ where
 
             // Stick with the earlier.
             (Some(Err(Lacks::Transparency)), Err(Lacks::Transparency)) => (),
-            (Some(Err(_)), Err(Lacks::XID)) => (),
+            (Some(Err(_)), Err(Lacks::Visual)) => (),
 
-            // Lacking transparency is better than lacking the xid.
-            (Some(Err(Lacks::XID)), Err(Lacks::Transparency)) => {
+            // Lacking transparency is better than lacking the requested visual.
+            (Some(Err(Lacks::Visual)), Err(Lacks::Transparency)) => {
                 chosen_config_id = Some((config_id, visual_infos));
                 lacks_what = Some(this_lacks_what);
             }
@@ -150,7 +156,7 @@ where
         Some(Err(Lacks::Transparency)) => log::warn!(
             "Glutin could not a find fb config with an alpha mask. Transparency may be broken."
         ),
-        Some(Err(Lacks::XID)) => panic!(),
+        Some(Err(Lacks::Visual)) => return Err(()),
         None => unreachable!(),
     }
 
@@ -171,34 +177,45 @@ impl Context {
         }
     }
 
+    /// Returns the screen requested in `pf_reqs`, or the default one.
+    fn screen_id(
+        xconn: &Arc<XConnection>,
+        pf_reqs: &PixelFormatRequirements,
+    ) -> Result<raw::c_int, CreationError> {
+        let screen_count = unsafe { (xconn.xlib.XScreenCount)(xconn.display) };
+        match pf_reqs.x11.screen {
+            Some(screen_id) if screen_id < 0 || screen_id >= screen_count => {
+                Err(CreationError::OsError(format!(
+                    "X screen {} does not exist, the display has {}",
+                    screen_id, screen_count
+                )))
+            }
+            Some(screen_id) => Ok(screen_id),
+            None => Ok(unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) }),
+        }
+    }
+
     #[inline]
-    pub fn new_headless<T>(
-        el: &EventLoopWindowTarget<T>,
+    pub fn new_headless(
+        xconn: &Arc<XConnection>,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
         size: Option<dpi::PhysicalSize<u32>>,
     ) -> Result<Self, CreationError> {
         Self::try_then_fallback(|fallback| {
-            Self::new_headless_impl(el, pf_reqs, gl_attr, size.clone(), fallback)
+            Self::new_headless_impl(xconn, pf_reqs, gl_attr, size, fallback)
         })
     }
 
-    fn new_headless_impl<T>(
-        el: &EventLoopWindowTarget<T>,
+    fn new_headless_impl(
+        xconn: &Arc<XConnection>,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Context>,
         size: Option<dpi::PhysicalSize<u32>>,
         fallback: bool,
     ) -> Result<Self, CreationError> {
-        let xconn = match el.xlib_xconnection() {
-            Some(xconn) => xconn,
-            None => {
-                return Err(CreationError::NoBackendAvailable(Box::new(NoX11Connection)));
-            }
-        };
-
-        // Get the screen_id for the window being built.
-        let screen_id = unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) };
+        // Get the screen_id for the context being built.
+        let screen_id = Self::screen_id(xconn, pf_reqs)?;
 
         let mut builder_glx_u = None;
         let mut builder_egl_u = None;
@@ -206,7 +223,7 @@ impl Context {
         // start the context building process
         if let Some(size) = size {
             let context = Self::new_first_stage(
-                &xconn,
+                xconn,
                 pf_reqs,
                 gl_attr,
                 screen_id,
@@ -224,13 +241,14 @@ impl Context {
                 Prototype::Egl(ctx) => X11Context::Egl(ctx.finish_pbuffer(size)?),
             };
 
-            let context = Context::PBuffer(ContextInner { xconn: Arc::clone(&xconn), context });
+            let context =
+                Context::PBuffer(ContextInner { xconn: Some(Arc::clone(xconn)), context });
 
             Ok(context)
         } else {
             // Surfaceless
             let context = Self::new_first_stage(
-                &xconn,
+                xconn,
                 pf_reqs,
                 gl_attr,
                 screen_id,
@@ -268,7 +286,8 @@ impl Context {
                 _ => unimplemented!(),
             };
 
-            let context = Context::Surfaceless(ContextInner { xconn: Arc::clone(&xconn), context });
+            let context =
+                Context::Surfaceless(ContextInner { xconn: Some(Arc::clone(xconn)), context });
 
             Ok(context)
         }
@@ -296,14 +315,41 @@ impl Context {
                 Some(x11_utils::get_visual_info_from_xid(xconn, xid))
             })
             .map(|(c, _)| c)
+            .map_err(|()| CreationError::NoAvailablePixelFormat)
         };
         Ok(match gl_attr.version {
+            // only EGL can create OpenGL ES contexts
+            GlRequest::Specific(Api::OpenGlEs, _) | GlRequest::Ranges(_)
+                if gl_attr.version.to_gl_version().is_none() =>
+            {
+                if let Some(_) = *EGL {
+                    let builder = gl_attr.clone();
+                    *builder_egl_u = Some(builder.map_sharing(|c| match c.context {
+                        X11Context::Egl(ref c) => c,
+                        _ => panic!(),
+                    }));
+                    Prototype::Egl(EglContext::new(
+                        pf_reqs,
+                        builder_egl_u.as_ref().unwrap(),
+                        NativeDisplay::X11(Some(xconn.display as *const _), pf_reqs.x11.screen),
+                        surface_type,
+                        select_config,
+                    )?)
+                } else {
+                    return Err(CreationError::NotSupported("libEGL not present".to_string()));
+                }
+            }
             GlRequest::Latest
             | GlRequest::Specific(Api::OpenGl, _)
-            | GlRequest::GlThenGles { .. } => {
+            | GlRequest::GlThenGles { .. }
+            | GlRequest::Ranges(_) => {
                 // GLX should be preferred over EGL, otherwise crashes may occur
                 // on X11 – issue #314
                 //
+                // GLX only creates OpenGL contexts, so `Ranges` requests with
+                // OpenGL ES entries rely on the EGL fallback of
+                // `try_then_fallback` if GLX can't create any OpenGL version.
+                //
                 // However, with surfaceless, GLX isn't really there, so we
                 // should prefer EGL.
                 let glx = |builder_u: &'a mut Option<_>| {
@@ -328,7 +374,8 @@ impl Context {
                         X11Context::Egl(ref c) => c,
                         _ => panic!("context already exists but is wrong type"),
                     }));
-                    let native_display = NativeDisplay::X11(Some(xconn.display as *const _));
+                    let native_display =
+                        NativeDisplay::X11(Some(xconn.display as *const _), pf_reqs.x11.screen);
                     Ok(Prototype::Egl(EglContext::new(
                         pf_reqs,
                         builder_u.as_ref().unwrap(),
@@ -338,6 +385,16 @@ impl Context {
                     )?))
                 };
 
+                // the EGL display to use was given
+                if pf_reqs.egl_display.is_some() {
+                    if let Some(X11Context::Glx(_)) = gl_attr.sharing.map(|c| &c.context) {
+                        return Err(CreationError::IncompatibleShareContext(
+                            "Cannot share a GLX context with one on an EGL display".to_string(),
+                        ));
+                    }
+                    return egl(builder_egl_u);
+                }
+
                 // if there is already a context, just use that.
                 // this prevents the "context already exists but is wrong type" panics above.
                 if let Some(c) = gl_attr.sharing {
@@ -404,24 +461,6 @@ impl Context {
                     ));
                 }
             }
-            GlRequest::Specific(Api::OpenGlEs, _) => {
-                if let Some(_) = *EGL {
-                    let builder = gl_attr.clone();
-                    *builder_egl_u = Some(builder.map_sharing(|c| match c.context {
-                        X11Context::Egl(ref c) => c,
-                        _ => panic!(),
-                    }));
-                    Prototype::Egl(EglContext::new(
-                        pf_reqs,
-                        builder_egl_u.as_ref().unwrap(),
-                        NativeDisplay::X11(Some(xconn.display as *const _)),
-                        surface_type,
-                        select_config,
-                    )?)
-                } else {
-                    return Err(CreationError::NotSupported("libEGL not present".to_string()));
-                }
-            }
             GlRequest::Specific(_, _) => {
                 return Err(CreationError::NotSupported(
                     "requested specific without gl or gles".to_string(),
@@ -431,6 +470,7 @@ impl Context {
     }
 
     #[inline]
+    #[cfg(feature = "winit")]
     pub fn new<T>(
         wb: WindowBuilder,
         el: &EventLoopWindowTarget<T>,
@@ -442,6 +482,7 @@ impl Context {
         })
     }
 
+    #[cfg(feature = "winit")]
     fn new_impl<T>(
         wb: WindowBuilder,
         el: &EventLoopWindowTarget<T>,
@@ -457,13 +498,13 @@ impl Context {
         };
 
         // Get the screen_id for the window being built.
-        let screen_id = unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) };
+        let screen_id = Self::screen_id(&xconn, pf_reqs)?;
 
         let mut builder_glx_u = None;
         let mut builder_egl_u = None;
 
         // start the context building process
-        let context = Self::new_first_stage(
+        let mut context = Self::new_first_stage(
             &xconn,
             pf_reqs,
             gl_attr,
@@ -480,8 +521,15 @@ impl Context {
         // the visual to use)
         let visual_infos = match context {
             Prototype::Glx(ref p) => p.get_visual_infos().clone(),
-            Prototype::Egl(ref p) => {
-                utils::get_visual_info_from_xid(&xconn, p.get_native_visual_id() as ffi::VisualID)
+            Prototype::Egl(ref mut p) => {
+                let visual_infos = utils::get_visual_info_from_xid(
+                    &xconn,
+                    p.get_native_visual_id() as ffi::VisualID,
+                );
+                p.set_transparency(
+                    utils::examine_visual_info(&xconn, visual_infos, true, None).is_ok(),
+                );
+                visual_infos
             }
         };
 
@@ -495,7 +543,7 @@ impl Context {
             Prototype::Egl(ctx) => X11Context::Egl(ctx.finish(xwin as _)?),
         };
 
-        let context = Context::Windowed(ContextInner { xconn: Arc::clone(&xconn), context });
+        let context = Context::Windowed(ContextInner { xconn: Some(Arc::clone(&xconn)), context });
 
         Ok((win, context))
     }
@@ -512,6 +560,116 @@ impl Context {
         })
     }
 
+    /// Creates a context for a window of an XCB connection.
+    ///
+    /// If glutin was given the Xlib display owning the connection before, it
+    /// is used like with `new_raw_context`. Otherwise the context is created
+    /// with `EGL_EXT_platform_xcb`, as the window may not belong to the
+    /// default display.
+    pub unsafe fn new_raw_xcb_context(
+        connection: *mut raw::c_void,
+        xwin: u32,
+        visual_id: u32,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        if let Some(xconn) = utils::xcb_xconnection(connection) {
+            return Self::new_raw_context(xconn, xwin as raw::c_ulong, pf_reqs, gl_attr);
+        }
+
+        Self::new_raw_xcb_egl_context(connection, xwin, visual_id, pf_reqs, gl_attr)
+    }
+
+    unsafe fn new_raw_xcb_egl_context(
+        connection: *mut raw::c_void,
+        xwin: u32,
+        visual_id: u32,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Context>,
+    ) -> Result<Self, CreationError> {
+        if EGL.is_none() {
+            return Err(CreationError::NotSupported("libEGL not present".to_string()));
+        }
+        if !egl::get_client_extensions().iter().any(|e| e == "EGL_EXT_platform_xcb") {
+            return Err(CreationError::NotSupported(
+                "an XCB connection needs `EGL_EXT_platform_xcb`, or its Xlib display".to_string(),
+            ));
+        }
+        if let Some(X11Context::Glx(_)) = gl_attr.sharing.map(|c| &c.context) {
+            return Err(CreationError::NotSupported(
+                "a GLX context can't be shared with an XCB context".to_string(),
+            ));
+        }
+        let gl_attr = gl_attr.clone().map_sharing(|c| match c.context {
+            X11Context::Egl(ref c) => c,
+            X11Context::Glx(_) => unreachable!(),
+        });
+
+        // without Xlib the visual can't be examined, so the config simply has
+        // to match the visual of the window
+        let visual_id = match visual_id {
+            0 => pf_reqs.x11.visual_xid.map(|xid| xid as u32),
+            visual_id => Some(visual_id),
+        };
+        let select_config = |configs: Vec<_>, display| {
+            configs
+                .into_iter()
+                .find(|&config| match egl::get_native_visual_id(display, config) as u32 {
+                    0 => false,
+                    id => visual_id.unwrap_or(id) == id,
+                })
+                .ok_or(CreationError::NoAvailablePixelFormat)
+        };
+
+        let context = EglContext::new(
+            pf_reqs,
+            &gl_attr,
+            NativeDisplay::Xcb(connection as *const _, pf_reqs.x11.screen),
+            EglSurfaceType::Window,
+            select_config,
+        )?
+        .finish(xwin as _)?;
+
+        Ok(Context::Windowed(ContextInner { xconn: None, context: X11Context::Egl(context) }))
+    }
+
+    /// Returns the visual of the config `new_raw_context` would choose for a
+    /// window, without creating a context.
+    pub fn choose_visual(
+        xconn: &Arc<XConnection>,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Context>,
+    ) -> Result<X11Visual, CreationError> {
+        let screen_id = Self::screen_id(xconn, pf_reqs)?;
+        let visual_info = Self::try_then_fallback(|fallback| {
+            let mut builder_glx_u = None;
+            let mut builder_egl_u = None;
+            let context = Self::new_first_stage(
+                xconn,
+                pf_reqs,
+                gl_attr,
+                screen_id,
+                &mut builder_glx_u,
+                &mut builder_egl_u,
+                EglSurfaceType::Window,
+                fallback,
+                fallback,
+                None,
+            )?;
+
+            match context {
+                Prototype::Glx(ref p) => Ok(*p.get_visual_infos()),
+                Prototype::Egl(ref p) => match p.get_native_visual_id() {
+                    0 => Err(CreationError::NoAvailablePixelFormat),
+                    xid => Ok(utils::get_visual_info_from_xid(xconn, xid as ffi::VisualID)),
+                },
+            }
+        })?;
+
+        let default_visual = unsafe { (xconn.xlib.XDefaultVisual)(xconn.display, screen_id) };
+        Ok(X11Visual { visual_info, needs_colormap: visual_info.visual != default_visual })
+    }
+
     fn new_raw_context_impl(
         xconn: &Arc<XConnection>,
         xwin: raw::c_ulong,
@@ -543,14 +701,13 @@ impl Context {
 
         let visual_xid = unsafe { (xconn.xlib.XVisualIDFromVisual)(attrs.visual) };
         let mut pf_reqs = pf_reqs.clone();
-        pf_reqs.x11_visual_xid = Some(visual_xid);
-        pf_reqs.depth_bits = Some(attrs.depth as _);
+        pf_reqs.x11.visual_xid = Some(visual_xid);
 
         let mut builder_glx_u = None;
         let mut builder_egl_u = None;
 
         // start the context building process
-        let context = Self::new_first_stage(
+        let mut context = Self::new_first_stage(
             &xconn,
             &pf_reqs,
             gl_attr,
@@ -563,17 +720,36 @@ impl Context {
             None,
         )?;
 
+        if let Prototype::Egl(ref mut p) = context {
+            let visual_infos =
+                utils::get_visual_info_from_xid(&xconn, p.get_native_visual_id() as ffi::VisualID);
+            p.set_transparency(
+                utils::examine_visual_info(&xconn, visual_infos, true, None).is_ok(),
+            );
+        }
+
         // finish creating the OpenGL context
         let context = match context {
             Prototype::Glx(ctx) => X11Context::Glx(ctx.finish(xwin)?),
             Prototype::Egl(ctx) => X11Context::Egl(ctx.finish(xwin as _)?),
         };
 
-        let context = Context::Windowed(ContextInner { xconn: Arc::clone(&xconn), context });
+        let context = Context::Windowed(ContextInner { xconn: Some(Arc::clone(&xconn)), context });
 
         Ok(context)
     }
 
+    #[inline]
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        match self {
+            Context::PBuffer(ctx) => match ctx.context {
+                X11Context::Glx(ref ctx) => ctx.resize_pbuffer(width, height),
+                X11Context::Egl(ref ctx) => ctx.resize_pbuffer(width, height),
+            },
+            Context::Surfaceless(_) | Context::Windowed(_) => Ok(()),
+        }
+    }
+
     #[inline]
     pub unsafe fn make_current(&self) -> Result<(), ContextError> {
         match self.context {
@@ -606,6 +782,14 @@ impl Context {
         }
     }
 
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        match self.context {
+            X11Context::Glx(ref ctx) => ctx.get_api_version(),
+            X11Context::Egl(ref ctx) => ctx.get_api_version(),
+        }
+    }
+
     #[inline]
     pub unsafe fn raw_handle(&self) -> &X11Context {
         &self.context
@@ -619,6 +803,14 @@ impl Context {
         }
     }
 
+    #[inline]
+    pub fn egl_display(&self) -> Option<EglDisplay> {
+        match self.context {
+            X11Context::Egl(ref ctx) => Some(ctx.egl_display().clone()),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
         match self.context {
@@ -638,9 +830,7 @@ impl Context {
     #[inline]
     pub fn swap_buffers_with_damage(&self, rects: &[Rect]) -> Result<(), ContextError> {
         match self.context {
-            X11Context::Glx(_) => {
-                Err(ContextError::OsError("buffer damage not suported".to_string()))
-            }
+            X11Context::Glx(ref ctx) => ctx.swap_buffers_with_damage(rects),
             X11Context::Egl(ref ctx) => ctx.swap_buffers_with_damage(rects),
         }
     }
@@ -648,11 +838,59 @@ impl Context {
     #[inline]
     pub fn swap_buffers_with_damage_supported(&self) -> bool {
         match self.context {
-            X11Context::Glx(_) => false,
+            X11Context::Glx(ref ctx) => ctx.swap_buffers_with_damage_supported(),
             X11Context::Egl(ref ctx) => ctx.swap_buffers_with_damage_supported(),
         }
     }
 
+    #[inline]
+    pub fn set_damage_region(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        match self.context {
+            X11Context::Glx(_) => Err(ContextError::FunctionUnavailable),
+            X11Context::Egl(ref ctx) => ctx.set_damage_region(rects),
+        }
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
+        match self.context {
+            X11Context::Glx(_) => false,
+            X11Context::Egl(ref ctx) => ctx.set_damage_region_supported(),
+        }
+    }
+
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        match self.context {
+            X11Context::Glx(ref ctx) => ctx.buffer_age(),
+            X11Context::Egl(ref ctx) => ctx.buffer_age(),
+        }
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        match self.context {
+            X11Context::Glx(ref ctx) => ctx.platform_extensions(),
+            X11Context::Egl(ref ctx) => ctx.platform_extensions(),
+        }
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        match self.context {
+            X11Context::Glx(_) => None,
+            X11Context::Egl(ref ctx) => ctx.priority(),
+        }
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        match self.context {
+            X11Context::Glx(ref ctx) => ctx.renderer_info(),
+            X11Context::Egl(ref ctx) => ctx.renderer_info(),
+        }
+    }
+
     #[inline]
     pub fn get_pixel_format(&self) -> PixelFormat {
         match self.context {
@@ -660,4 +898,19 @@ impl Context {
             X11Context::Egl(ref ctx) => ctx.get_pixel_format(),
         }
     }
+
+    #[inline]
+    pub fn get_visual_info(&self) -> Option<ffi::XVisualInfo> {
+        match self.context {
+            X11Context::Glx(ref ctx) => Some(*ctx.get_visual_infos()),
+            X11Context::Egl(ref ctx) => match ctx.get_native_visual_id() {
+                // e.g. configs only usable with pbuffers
+                0 => None,
+                xid => self
+                    .xconn
+                    .as_ref()
+                    .map(|xconn| utils::get_visual_info_from_xid(xconn, xid as ffi::VisualID)),
+            },
+        }
+    }
 }
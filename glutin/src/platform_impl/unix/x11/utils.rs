@@ -1,8 +1,149 @@
+#[cfg(feature = "winit")]
+use crate::platform::unix::x11;
 use crate::platform::unix::x11::XConnection;
+use crate::{CreationError, PixelFormatRequirements};
 use glutin_glx_sys as ffi;
+use parking_lot::Mutex;
 
+use std::collections::HashMap;
+use std::os::raw;
 use std::sync::Arc;
 
+lazy_static! {
+    // Connections wrapping displays opened by someone else, by display
+    // address. They're released once only this map holds them.
+    static ref FOREIGN_XCONNECTIONS: Mutex<HashMap<usize, Arc<XConnection>>> =
+        Mutex::new(HashMap::new());
+}
+
+// Winit's connections load these along with Xlib, glutin's own ones only
+// when they're first needed.
+#[cfg(not(feature = "winit"))]
+lazy_static! {
+    static ref XLIB_XCB: Option<ffi::xlib_xcb::Xlib_xcb> = ffi::xlib_xcb::Xlib_xcb::open().ok();
+    static ref XRENDER: Option<ffi::xrender::Xrender> = ffi::xrender::Xrender::open().ok();
+}
+
+#[cfg(feature = "winit")]
+fn xlib_xcb(xconn: &XConnection) -> Option<&ffi::xlib_xcb::Xlib_xcb> {
+    Some(&xconn.xlib_xcb)
+}
+
+#[cfg(not(feature = "winit"))]
+fn xlib_xcb(_: &XConnection) -> Option<&ffi::xlib_xcb::Xlib_xcb> {
+    XLIB_XCB.as_ref()
+}
+
+#[cfg(feature = "winit")]
+fn xrender(xconn: &XConnection) -> Option<&ffi::xrender::Xrender> {
+    Some(&xconn.xrender)
+}
+
+#[cfg(not(feature = "winit"))]
+fn xrender(_: &XConnection) -> Option<&ffi::xrender::Xrender> {
+    XRENDER.as_ref()
+}
+
+/// Wraps a display opened by someone else, e.g. the toolkit owning a window,
+/// without ever closing it.
+///
+/// The connection is shared by the contexts using the display, and released
+/// once none of them uses it anymore, so that a display opened later at the
+/// same address gets a connection of its own.
+pub unsafe fn foreign_xconnection(
+    display: *mut ffi::Display,
+) -> Result<Arc<XConnection>, CreationError> {
+    let mut xconns = FOREIGN_XCONNECTIONS.lock();
+    let (used, unused): (HashMap<_, _>, HashMap<_, _>) =
+        xconns.drain().partition(|(_, xconn)| Arc::strong_count(xconn) > 1);
+    *xconns = used;
+    unused.into_iter().for_each(|(_, xconn)| release_xconnection(xconn));
+
+    if let Some(xconn) = xconns.get(&(display as usize)) {
+        return Ok(xconn.clone());
+    }
+
+    let xconn = Arc::new(xconnection(display)?);
+    xconns.insert(display as usize, xconn.clone());
+    Ok(xconn)
+}
+
+// Drops a connection wrapping a display opened by someone else, without
+// closing the display.
+fn release_xconnection(xconn: Arc<XConnection>) {
+    if let Ok(xconn) = Arc::try_unwrap(xconn) {
+        // winit's connections close their display once dropped
+        #[cfg(feature = "winit")]
+        std::mem::forget(xconn);
+        #[cfg(not(feature = "winit"))]
+        drop(xconn);
+    }
+}
+
+/// Returns the connection wrapping the Xlib display `connection` belongs to,
+/// if contexts created with that display are still alive.
+pub unsafe fn xcb_xconnection(connection: *mut raw::c_void) -> Option<Arc<XConnection>> {
+    FOREIGN_XCONNECTIONS
+        .lock()
+        .values()
+        .filter(|xconn| Arc::strong_count(xconn) > 1)
+        .find(|xconn| match xlib_xcb(xconn) {
+            Some(xlib_xcb) => (xlib_xcb.XGetXCBConnection)(xconn.display as *mut _) == connection,
+            None => false,
+        })
+        .cloned()
+}
+
+/// Opens a new connection to the default display, closed once dropped.
+#[cfg(feature = "winit")]
+pub fn open_xconnection() -> Result<Arc<XConnection>, CreationError> {
+    let xlib =
+        x11::ffi::Xlib::open().map_err(|e| CreationError::NoBackendAvailable(Box::new(e)))?;
+    let display = unsafe {
+        (xlib.XInitThreads)();
+        (xlib.XOpenDisplay)(std::ptr::null())
+    };
+    if display.is_null() {
+        return Err(CreationError::OsError("XOpenDisplay failed".to_string()));
+    }
+    unsafe { xconnection(display) }.map(Arc::new)
+}
+
+/// Opens a new connection to the default display, closed once dropped.
+#[cfg(not(feature = "winit"))]
+pub fn open_xconnection() -> Result<Arc<XConnection>, CreationError> {
+    XConnection::new().map(Arc::new).map_err(|e| CreationError::NoBackendAvailable(Box::new(e)))
+}
+
+// Unlike `XConnection::new`, leaves the error handler of the process alone.
+#[cfg(feature = "winit")]
+unsafe fn xconnection(display: *mut ffi::Display) -> Result<XConnection, CreationError> {
+    fn no_backend(e: x11::ffi::OpenError) -> CreationError {
+        CreationError::NoBackendAvailable(Box::new(e))
+    }
+
+    let xlib = x11::ffi::Xlib::open().map_err(no_backend)?;
+    Ok(XConnection {
+        xrandr: x11::ffi::Xrandr_2_2_0::open().map_err(no_backend)?,
+        xrandr_1_5: x11::ffi::Xrandr::open().ok(),
+        xcursor: x11::ffi::Xcursor::open().map_err(no_backend)?,
+        xinput2: x11::ffi::XInput2::open().map_err(no_backend)?,
+        xlib_xcb: x11::ffi::Xlib_xcb::open().map_err(no_backend)?,
+        xrender: x11::ffi::Xrender::open().map_err(no_backend)?,
+        display: display as *mut _,
+        x11_fd: (xlib.XConnectionNumber)(display as *mut _),
+        latest_error: Default::default(),
+        cursor_cache: Default::default(),
+        xlib,
+    })
+}
+
+#[cfg(not(feature = "winit"))]
+unsafe fn xconnection(display: *mut ffi::Display) -> Result<XConnection, CreationError> {
+    XConnection::from_display(display as *mut _)
+        .map_err(|e| CreationError::NoBackendAvailable(Box::new(e)))
+}
+
 pub fn get_visual_info_from_xid(xconn: &Arc<XConnection>, xid: ffi::VisualID) -> ffi::XVisualInfo {
     assert_ne!(xid, 0);
     let mut template: ffi::XVisualInfo = unsafe { std::mem::zeroed() };
@@ -31,28 +172,31 @@ pub fn get_visual_info_from_xid(xconn: &Arc<XConnection>, xid: ffi::VisualID) ->
 #[derive(Clone, Copy, Debug)]
 pub enum Lacks {
     Transparency,
-    XID,
+    Visual,
 }
 
-/// Should always check for lack of xid before lack of transparency.
+/// Should always check for lack of the requested visual before lack of
+/// transparency.
 pub fn examine_visual_info(
     xconn: &Arc<XConnection>,
     visual_infos: ffi::XVisualInfo,
     want_transparency: bool,
-    want_xid: Option<ffi::VisualID>,
+    want_visual: Option<&PixelFormatRequirements>,
 ) -> Result<(), Lacks> {
-    if let Some(want_xid) = want_xid {
-        if visual_infos.visualid != want_xid {
-            return Err(Lacks::XID);
+    if let Some(pf_reqs) = want_visual {
+        if pf_reqs.x11.visual_xid.map_or(false, |xid| visual_infos.visualid != xid)
+            || pf_reqs.x11.visual_depth.map_or(false, |depth| visual_infos.depth != depth as _)
+            || pf_reqs.x11.visual_class.map_or(false, |class| visual_infos.class != class)
+        {
+            return Err(Lacks::Visual);
         }
     }
 
     unsafe {
         if want_transparency {
-            let pict_format = (xconn.xrender.XRenderFindVisualFormat)(
-                xconn.display as *mut _,
-                visual_infos.visual,
-            );
+            let xrender = xrender(xconn).ok_or(Lacks::Transparency)?;
+            let pict_format =
+                (xrender.XRenderFindVisualFormat)(xconn.display as *mut _, visual_infos.visual);
             if pict_format.is_null() {
                 return Err(Lacks::Transparency);
             }
@@ -0,0 +1,163 @@
+//! A minimal stand-in for winit's `XConnection`, used when glutin is built
+//! without winit.
+
+use glutin_glx_sys as ffi;
+use parking_lot::Mutex;
+
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+// The last error reported to the handler installed by `XConnection`, for
+// each display, keyed by the address of the display.
+static LATEST_ERRORS: Mutex<Vec<(usize, XError)>> = parking_lot::const_mutex(Vec::new());
+
+/// A connection to an X server.
+pub struct XConnection {
+    pub xlib: ffi::Xlib,
+    pub display: *mut ffi::Display,
+    // whether the display was opened by us, and has to be closed on drop
+    owned: bool,
+}
+
+unsafe impl Send for XConnection {}
+unsafe impl Sync for XConnection {}
+
+impl XConnection {
+    /// Opens a connection to the default display, and installs an error
+    /// handler recording the errors of every display of the process.
+    pub fn new() -> Result<XConnection, XNotSupported> {
+        let xlib = ffi::Xlib::open()?;
+        let display = unsafe {
+            (xlib.XInitThreads)();
+            (xlib.XSetErrorHandler)(Some(x_error_callback));
+            (xlib.XOpenDisplay)(ptr::null())
+        };
+        if display.is_null() {
+            return Err(XNotSupported::XOpenDisplayFailed);
+        }
+
+        Ok(XConnection { xlib, display, owned: true })
+    }
+
+    /// Wraps a display opened by someone else. It is not closed on drop.
+    ///
+    /// Like [`XConnection::new`], this installs an error handler recording the
+    /// errors of every display of the process, replacing the one of the
+    /// toolkit owning `display`.
+    ///
+    /// # Safety
+    ///
+    /// `display` must be a valid display, outliving the connection.
+    pub unsafe fn from_display(display: *mut ffi::Display) -> Result<XConnection, XNotSupported> {
+        let xlib = ffi::Xlib::open()?;
+        (xlib.XSetErrorHandler)(Some(x_error_callback));
+        Ok(XConnection { xlib, display, owned: false })
+    }
+
+    /// Returns the last error the display reported to the handler installed
+    /// by [`XConnection::new`] or [`XConnection::from_display`], if any, and
+    /// forgets it.
+    pub(crate) fn check_errors(&self) -> Result<(), XError> {
+        let mut errors = LATEST_ERRORS.lock();
+        match errors.iter().position(|&(display, _)| display == self.display as usize) {
+            Some(index) => Err(errors.swap_remove(index).1),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for XConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XConnection")
+            .field("display", &self.display)
+            .field("owned", &self.owned)
+            .finish()
+    }
+}
+
+impl Drop for XConnection {
+    fn drop(&mut self) {
+        LATEST_ERRORS.lock().retain(|&(display, _)| display != self.display as usize);
+        if self.owned {
+            unsafe { (self.xlib.XCloseDisplay)(self.display) };
+        }
+    }
+}
+
+/// An error reported by the X server.
+#[derive(Debug, Clone)]
+pub struct XError {
+    pub description: String,
+    pub error_code: u8,
+    pub request_code: u8,
+    pub minor_code: u8,
+}
+
+unsafe extern "C" fn x_error_callback(
+    display: *mut ffi::Display,
+    event: *mut ffi::XErrorEvent,
+) -> c_int {
+    // the handler can't be given the `XConnection`, so Xlib is loaded again;
+    // it's already in memory, so this only looks up the symbols
+    if let Ok(xlib) = ffi::Xlib::open() {
+        let mut buf: [c_char; 1024] = [0; 1024];
+        (xlib.XGetErrorText)(
+            display,
+            (*event).error_code as c_int,
+            buf.as_mut_ptr(),
+            buf.len() as c_int,
+        );
+        let error = XError {
+            description: CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned(),
+            error_code: (*event).error_code,
+            request_code: (*event).request_code,
+            minor_code: (*event).minor_code,
+        };
+        let mut errors = LATEST_ERRORS.lock();
+        match errors.iter_mut().find(|(latest, _)| *latest == display as usize) {
+            Some((_, latest)) => *latest = error,
+            None => errors.push((display as usize, error)),
+        }
+    }
+    // the return value is ignored
+    0
+}
+
+/// The reason a connection to the X server couldn't be made.
+#[derive(Debug)]
+pub enum XNotSupported {
+    /// Failed to load Xlib.
+    LibraryOpenError(ffi::error::OpenError),
+    /// Connecting to the X server with `XOpenDisplay` failed.
+    XOpenDisplayFailed,
+}
+
+impl From<ffi::error::OpenError> for XNotSupported {
+    fn from(err: ffi::error::OpenError) -> XNotSupported {
+        XNotSupported::LibraryOpenError(err)
+    }
+}
+
+impl fmt::Display for XNotSupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XNotSupported::LibraryOpenError(err) => {
+                write!(f, "Failed to load one of xlib's shared libraries: {}", err)
+            }
+            XNotSupported::XOpenDisplayFailed => {
+                f.write_str("Failed to open connection to X server")
+            }
+        }
+    }
+}
+
+impl std::error::Error for XNotSupported {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            XNotSupported::LibraryOpenError(err) => Some(err),
+            XNotSupported::XOpenDisplayFailed => None,
+        }
+    }
+}
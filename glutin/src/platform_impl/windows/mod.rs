@@ -1,15 +1,20 @@
 #![cfg(target_os = "windows")]
 
 use crate::{
-    Api, ContextCurrentState, ContextError, CreationError, GlAttributes, GlRequest, NotCurrent,
-    PixelFormat, PixelFormatRequirements, Rect,
+    Api, ContextCurrentState, ContextError, ContextPriority, CreationError, GlAttributes,
+    GlRequest, NotCurrent, PixelFormat, PixelFormatRequirements, Rect, RendererInfo,
 };
 
-use crate::api::egl::{Context as EglContext, NativeDisplay, SurfaceType as EglSurfaceType, EGL};
+use crate::api::egl::{
+    Context as EglContext, EglDisplay, NativeDisplay, SurfaceType as EglSurfaceType, EGL,
+};
+#[cfg(feature = "mock")]
+use crate::api::mock::MockContext;
 use crate::api::wgl::Context as WglContext;
 use crate::platform::windows::WindowExtWindows;
 
 use glutin_egl_sys as ffi;
+use raw_window_handle::RawWindowHandle;
 use winapi::shared::windef::{HGLRC, HWND};
 use winit;
 use winit::dpi;
@@ -17,7 +22,6 @@ use winit::event_loop::EventLoopWindowTarget;
 use winit::platform::windows::WindowBuilderExtWindows;
 use winit::window::{Window, WindowBuilder};
 
-use std::marker::PhantomData;
 use std::os::raw;
 
 /// Context handles available on Windows.
@@ -37,6 +41,8 @@ pub enum Context {
     HiddenWindowWgl(Window, WglContext),
     /// An EGL pbuffer.
     EglPbuffer(EglContext),
+    #[cfg(feature = "mock")]
+    Mock(MockContext),
 }
 
 unsafe impl Send for Context {}
@@ -58,12 +64,35 @@ impl Context {
         Ok((win, ctx))
     }
 
+    #[inline]
+    pub unsafe fn new_from_raw_window_handle(
+        handle: RawWindowHandle,
+        _size: dpi::PhysicalSize<u32>,
+        pf_reqs: &PixelFormatRequirements,
+        gl_attr: &GlAttributes<&Self>,
+    ) -> Result<Self, CreationError> {
+        match handle {
+            RawWindowHandle::Win32(handle) => {
+                Self::new_raw_context(handle.hwnd as HWND, pf_reqs, gl_attr)
+            }
+            _ => Err(CreationError::NotSupported(
+                "only Win32 window handles are supported on Windows".to_string(),
+            )),
+        }
+    }
+
     #[inline]
     pub fn new_raw_context(
         hwnd: HWND,
         pf_reqs: &PixelFormatRequirements,
         gl_attr: &GlAttributes<&Self>,
     ) -> Result<Self, CreationError> {
+        #[cfg(feature = "mock")]
+        if let Some(&Context::Mock(_)) = gl_attr.sharing {
+            let msg = "Cannot share a mock context with a non-mock context";
+            return Err(CreationError::IncompatibleShareContext(msg.into()));
+        }
+
         match gl_attr.version {
             GlRequest::Specific(Api::OpenGlEs, (_major, _minor)) => {
                 match (gl_attr.sharing, &*EGL) {
@@ -139,6 +168,12 @@ impl Context {
         gl_attr: &GlAttributes<&Context>,
         size: dpi::PhysicalSize<u32>,
     ) -> Result<Self, CreationError> {
+        #[cfg(feature = "mock")]
+        if let Some(&Context::Mock(_)) = gl_attr.sharing {
+            let msg = "Cannot share a mock context with a non-mock context";
+            return Err(CreationError::IncompatibleShareContext(msg.into()));
+        }
+
         // if EGL is available, we try using EGL first
         // if EGL returns an error, we try the hidden window method
         match (gl_attr.sharing, &*EGL) {
@@ -183,8 +218,14 @@ impl Context {
     }
 
     #[inline]
-    pub fn resize(&self, _width: u32, _height: u32) {
-        // Method is for API consistency.
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), ContextError> {
+        match *self {
+            Context::EglPbuffer(ref c) => c.resize_pbuffer(width, height),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.resize(width, height),
+            // Method is for API consistency.
+            _ => Ok(()),
+        }
     }
 
     #[inline]
@@ -194,6 +235,8 @@ impl Context {
             Context::Egl(ref c)
             | Context::HiddenWindowEgl(_, ref c)
             | Context::EglPbuffer(ref c) => c.make_current(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.make_current(),
         }
     }
 
@@ -204,6 +247,8 @@ impl Context {
             Context::Egl(ref c)
             | Context::HiddenWindowEgl(_, ref c)
             | Context::EglPbuffer(ref c) => c.make_not_current(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.make_not_current(),
         }
     }
 
@@ -214,6 +259,8 @@ impl Context {
             Context::Egl(ref c)
             | Context::HiddenWindowEgl(_, ref c)
             | Context::EglPbuffer(ref c) => c.is_current(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.is_current(),
         }
     }
 
@@ -224,6 +271,8 @@ impl Context {
             Context::Egl(ref c)
             | Context::HiddenWindowEgl(_, ref c)
             | Context::EglPbuffer(ref c) => c.get_proc_address(addr),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.get_proc_address(addr),
         }
     }
 
@@ -232,20 +281,53 @@ impl Context {
         match *self {
             Context::Wgl(ref c) => c.swap_buffers(),
             Context::Egl(ref c) => c.swap_buffers(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.swap_buffers(),
             _ => unreachable!(),
         }
     }
 
     #[inline]
-    pub fn swap_buffers_with_damage(&self, _rects: &[Rect]) -> Result<(), ContextError> {
-        Err(ContextError::OsError("buffer damage not suported".to_string()))
+    #[cfg_attr(not(feature = "mock"), allow(unused_variables))]
+    pub fn swap_buffers_with_damage(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.swap_buffers_with_damage(rects),
+            _ => Err(ContextError::OsError("buffer damage not suported".to_string())),
+        }
     }
 
     #[inline]
     pub fn swap_buffers_with_damage_supported(&self) -> bool {
+        match *self {
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.swap_buffers_with_damage_supported(),
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn set_damage_region(&self, _rects: &[Rect]) -> Result<(), ContextError> {
+        Err(ContextError::FunctionUnavailable)
+    }
+
+    #[inline]
+    pub fn set_damage_region_supported(&self) -> bool {
         false
     }
 
+    #[inline]
+    pub fn buffer_age(&self) -> Option<u32> {
+        match *self {
+            Context::Wgl(_) | Context::HiddenWindowWgl(_, _) => None,
+            Context::Egl(ref c)
+            | Context::HiddenWindowEgl(_, ref c)
+            | Context::EglPbuffer(ref c) => c.buffer_age(),
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => None,
+        }
+    }
+
     #[inline]
     pub fn get_api(&self) -> Api {
         match *self {
@@ -253,6 +335,58 @@ impl Context {
             Context::Egl(ref c)
             | Context::HiddenWindowEgl(_, ref c)
             | Context::EglPbuffer(ref c) => c.get_api(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.get_api(),
+        }
+    }
+
+    #[inline]
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        match *self {
+            Context::Wgl(ref c) | Context::HiddenWindowWgl(_, ref c) => c.get_api_version(),
+            Context::Egl(ref c)
+            | Context::HiddenWindowEgl(_, ref c)
+            | Context::EglPbuffer(ref c) => c.get_api_version(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.get_api_version(),
+        }
+    }
+
+    #[inline]
+    pub fn platform_extensions(&self) -> &[String] {
+        match *self {
+            Context::Wgl(ref c) | Context::HiddenWindowWgl(_, ref c) => c.platform_extensions(),
+            Context::Egl(ref c)
+            | Context::HiddenWindowEgl(_, ref c)
+            | Context::EglPbuffer(ref c) => c.platform_extensions(),
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => &[],
+        }
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<ContextPriority> {
+        match *self {
+            Context::Wgl(_) | Context::HiddenWindowWgl(..) => None,
+            Context::Egl(ref c)
+            | Context::HiddenWindowEgl(_, ref c)
+            | Context::EglPbuffer(ref c) => c.priority(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.priority(),
+        }
+    }
+
+    #[inline]
+    pub fn renderer_info(&self) -> RendererInfo {
+        match *self {
+            Context::Wgl(_) | Context::HiddenWindowWgl(..) => {
+                RendererInfo::from_gl(|addr| self.get_proc_address(addr))
+            }
+            Context::Egl(ref c)
+            | Context::HiddenWindowEgl(_, ref c)
+            | Context::EglPbuffer(ref c) => c.renderer_info(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.renderer_info(),
         }
     }
 
@@ -261,6 +395,8 @@ impl Context {
         match *self {
             Context::Wgl(ref c) => c.get_pixel_format(),
             Context::Egl(ref c) => c.get_pixel_format(),
+            #[cfg(feature = "mock")]
+            Context::Mock(ref c) => c.get_pixel_format(),
             _ => unreachable!(),
         }
     }
@@ -274,6 +410,8 @@ impl Context {
             Context::Egl(ref c)
             | Context::HiddenWindowEgl(_, ref c)
             | Context::EglPbuffer(ref c) => RawHandle::Egl(c.raw_handle()),
+            #[cfg(feature = "mock")]
+            Context::Mock(_) => RawHandle::Egl(std::ptr::null()),
         }
     }
 
@@ -286,6 +424,16 @@ impl Context {
             _ => None,
         }
     }
+
+    #[inline]
+    pub fn egl_display(&self) -> Option<EglDisplay> {
+        match *self {
+            Context::Egl(ref c)
+            | Context::HiddenWindowEgl(_, ref c)
+            | Context::EglPbuffer(ref c) => Some(c.egl_display().clone()),
+            _ => None,
+        }
+    }
 }
 
 pub trait RawContextExt {
@@ -311,10 +459,9 @@ impl<'a, T: ContextCurrentState> RawContextExt for crate::ContextBuilder<'a, T>
     where
         Self: Sized,
     {
-        let crate::ContextBuilder { pf_reqs, gl_attr } = self;
-        let gl_attr = gl_attr.map_sharing(|ctx| &ctx.context);
-        Context::new_raw_context(hwnd as *mut _, &pf_reqs, &gl_attr)
-            .map(|context| crate::Context { context, phantom: PhantomData })
-            .map(|context| crate::RawContext { context, window: () })
+        self.build_with(|pf_reqs, gl_attr| {
+            Context::new_raw_context(hwnd as *mut _, pf_reqs, gl_attr).map(|context| ((), context))
+        })
+        .map(|(window, context)| crate::RawContext { context, window })
     }
 }
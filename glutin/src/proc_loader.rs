@@ -0,0 +1,106 @@
+use super::*;
+
+use std::ffi::c_void;
+use std::fmt;
+
+/// Looks up the OpenGL functions of a [`Context`], caching the results.
+///
+/// Obtained from [`Context::proc_loader`]. Lookups go through the platform's
+/// `GetProcAddress`, then fall back to the client API library itself with
+/// EGL and GLX, as `eglGetProcAddress` may not return core functions before
+/// EGL 1.5.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(not(feature = "winit"))]
+/// # fn main() {}
+/// # #[cfg(feature = "winit")]
+/// # fn main() {
+/// # let el = glutin::event_loop::EventLoop::new();
+/// # let wb = glutin::window::WindowBuilder::new();
+/// # let context = glutin::ContextBuilder::new().build_windowed(wb, &el).unwrap();
+/// # let context = unsafe { context.make_current().unwrap() };
+/// let loader = context.proc_loader();
+/// if let Err(err) = loader.require(&["glCreateShader", "glDrawArrays"]) {
+///     panic!("{}", err);
+/// }
+/// let gl_finish: Option<extern "system" fn()> = unsafe { loader.get_fn("glFinish") };
+/// # }
+/// ```
+///
+/// [`Context`]: struct.Context.html
+/// [`Context::proc_loader`]: struct.Context.html#method.proc_loader
+#[derive(Debug)]
+pub struct ProcLoader<'a> {
+    context: &'a Context<PossiblyCurrent>,
+}
+
+impl<'a> ProcLoader<'a> {
+    pub(crate) fn new(context: &'a Context<PossiblyCurrent>) -> Self {
+        ProcLoader { context }
+    }
+
+    /// Returns the address of a function, or null if it's missing.
+    pub fn get(&self, name: &str) -> *const c_void {
+        // addresses are stored as `usize`, as pointers aren't `Send`
+        let mut procs = self.context.procs.lock().unwrap();
+        if let Some(&address) = procs.get(name) {
+            return address as *const c_void;
+        }
+        let address = self.context.context.get_proc_address(name);
+        procs.insert(name.to_string(), address as usize);
+        address
+    }
+
+    /// Returns a function as a function pointer of type `F`, or `None` if
+    /// it's missing.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be a function pointer type matching the signature of the
+    /// function, e.g. `extern "system" fn()` for `glFinish`.
+    pub unsafe fn get_fn<F: Copy>(&self, name: &str) -> Option<F> {
+        assert_eq!(
+            std::mem::size_of::<F>(),
+            std::mem::size_of::<*const c_void>(),
+            "`F` must be a function pointer"
+        );
+        let address = self.get(name);
+        if address.is_null() {
+            None
+        } else {
+            Some(std::mem::transmute_copy(&address))
+        }
+    }
+
+    /// Looks up every function of `names`, failing with the missing ones.
+    pub fn require(&self, names: &[&str]) -> Result<(), MissingProcs> {
+        let names: Vec<_> = names
+            .iter()
+            .filter(|name| self.get(name).is_null())
+            .map(|name| name.to_string())
+            .collect();
+        if names.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingProcs { names })
+        }
+    }
+}
+
+/// The functions [`ProcLoader::require`] couldn't find.
+///
+/// [`ProcLoader::require`]: struct.ProcLoader.html#method.require
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingProcs {
+    pub names: Vec<String>,
+}
+
+impl fmt::Display for MissingProcs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing OpenGL functions: {}", self.names.join(", "))
+    }
+}
+
+impl std::error::Error for MissingProcs {}
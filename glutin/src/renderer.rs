@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+
+const GL_VENDOR: u32 = 0x1F00;
+const GL_RENDERER: u32 = 0x1F01;
+const GL_VERSION: u32 = 0x1F02;
+const GL_EXTENSIONS: u32 = 0x1F03;
+const GL_NUM_EXTENSIONS: u32 = 0x821D;
+
+/// Identifies the renderer and driver behind a context, e.g. to work around
+/// driver bugs.
+///
+/// Fields the platform can't query are left to `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RendererInfo {
+    /// The company responsible for the driver, as reported by `GL_VENDOR`.
+    pub vendor: String,
+    /// The name of the renderer, as reported by `GL_RENDERER`.
+    pub renderer: String,
+    /// The version of the driver, e.g. `"23.1.4"` with
+    /// `GLX_MESA_query_renderer`. Otherwise the `GL_VERSION` string, which
+    /// usually contains it after the OpenGL version.
+    pub driver_version: String,
+    /// The PCI vendor id of the device.
+    pub vendor_id: Option<u32>,
+    /// The PCI device id of the device.
+    pub device_id: Option<u32>,
+    /// The amount of video memory, in megabytes.
+    pub video_memory: Option<u32>,
+    /// Whether the renderer runs on the CPU, e.g. llvmpipe or softpipe.
+    pub software: bool,
+}
+
+impl RendererInfo {
+    /// Builds the info from the GL strings of the current context.
+    pub(crate) fn from_gl<F>(get_proc_address: F) -> Self
+    where
+        F: Fn(&str) -> *const c_void,
+    {
+        let renderer = get_string(&get_proc_address, GL_RENDERER);
+        RendererInfo {
+            vendor: get_string(&get_proc_address, GL_VENDOR),
+            software: is_software_renderer(&renderer),
+            renderer,
+            driver_version: get_string(&get_proc_address, GL_VERSION),
+            vendor_id: None,
+            device_id: None,
+            video_memory: None,
+        }
+    }
+}
+
+/// Lists the extensions of the current context.
+///
+/// From OpenGL (ES) 3.0 on they're listed with `glGetStringi`, as core
+/// profiles don't support `GL_EXTENSIONS` with `glGetString`.
+pub(crate) fn gl_extensions<F>(get_proc_address: F) -> HashSet<String>
+where
+    F: Fn(&str) -> *const c_void,
+{
+    let (_, (major, _)) = gl_version(&get_proc_address);
+
+    let get_integer = get_proc_address("glGetIntegerv");
+    let get_string_i = get_proc_address("glGetStringi");
+    if major < 3 || get_integer.is_null() || get_string_i.is_null() {
+        return get_string(&get_proc_address, GL_EXTENSIONS)
+            .split(' ')
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| ext.to_string())
+            .collect();
+    }
+
+    unsafe {
+        let get_integer: unsafe extern "system" fn(u32, *mut i32) =
+            std::mem::transmute(get_integer);
+        let get_string_i: unsafe extern "system" fn(u32, u32) -> *const c_char =
+            std::mem::transmute(get_string_i);
+
+        let mut count = 0;
+        get_integer(GL_NUM_EXTENSIONS, &mut count);
+        (0..count.max(0) as u32)
+            .map(|index| get_string_i(GL_EXTENSIONS, index))
+            .filter(|ext| !ext.is_null())
+            .map(|ext| CStr::from_ptr(ext).to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+/// Returns whether the current context is an OpenGL ES one, and its version.
+pub(crate) fn gl_version<F>(get_proc_address: F) -> (bool, (u32, u32))
+where
+    F: Fn(&str) -> *const c_void,
+{
+    parse_gl_version(&get_string(&get_proc_address, GL_VERSION))
+}
+
+/// Parses a `GL_VERSION` string, e.g. "4.6 (Core Profile) Mesa 23.1.4" or
+/// "OpenGL ES 3.2 Mesa 23.1.4". The version is `(0, 0)` if it's missing.
+fn parse_gl_version(version: &str) -> (bool, (u32, u32)) {
+    let es = version.starts_with("OpenGL ES");
+    let mut numbers = version
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|number| number.parse::<u32>().ok());
+    let major = numbers.next().flatten().unwrap_or(0);
+    let minor = numbers.next().flatten().unwrap_or(0);
+    (es, (major, minor))
+}
+
+/// Calls `glGetString`, returning an empty string on failure.
+fn get_string<F>(get_proc_address: &F, name: u32) -> String
+where
+    F: Fn(&str) -> *const c_void,
+{
+    let get_string = get_proc_address("glGetString");
+    if get_string.is_null() {
+        return String::new();
+    }
+    unsafe {
+        let get_string: unsafe extern "system" fn(u32) -> *const c_char =
+            std::mem::transmute(get_string);
+        let string = get_string(name);
+        if string.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(string).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// Recognizes the software renderers of Mesa, Windows, macOS and
+/// SwiftShader by name.
+fn is_software_renderer(renderer: &str) -> bool {
+    let renderer = renderer.to_lowercase();
+    [
+        "llvmpipe",
+        "softpipe",
+        "swrast",
+        "software rasterizer",
+        "swiftshader",
+        "gdi generic",
+        "apple software renderer",
+    ]
+    .iter()
+    .any(|name| renderer.contains(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn software_renderers() {
+        assert!(is_software_renderer("llvmpipe (LLVM 15.0.7, 256 bits)"));
+        assert!(is_software_renderer("Gallium 0.4 on softpipe"));
+        assert!(is_software_renderer("Mesa DRI swrast"));
+        assert!(is_software_renderer("Software Rasterizer"));
+        assert!(is_software_renderer("Google SwiftShader"));
+        assert!(is_software_renderer("GDI Generic"));
+    }
+
+    #[test]
+    fn hardware_renderers() {
+        assert!(!is_software_renderer("Mesa Intel(R) UHD Graphics 620 (KBL GT2)"));
+        assert!(!is_software_renderer("NVIDIA GeForce RTX 3080/PCIe/SSE2"));
+        assert!(!is_software_renderer("AMD Radeon RX 6800 (navi21, LLVM 15.0.7, DRM 3.49)"));
+        assert!(!is_software_renderer(""));
+    }
+
+    #[test]
+    fn gl_versions() {
+        assert_eq!(parse_gl_version("OpenGL ES 3.2 Mesa 23.1.4"), (true, (3, 2)));
+        assert_eq!(parse_gl_version("4.6 (Core Profile) Mesa 23.1.4"), (false, (4, 6)));
+        assert_eq!(parse_gl_version("4.6.0 NVIDIA 535.54.03"), (false, (4, 6)));
+        assert_eq!(parse_gl_version(""), (false, (0, 0)));
+    }
+}
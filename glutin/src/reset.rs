@@ -0,0 +1,172 @@
+use super::*;
+
+use std::fmt;
+
+/// Why a context was lost, as returned by [`Context::reset_status`].
+///
+/// [`Context::reset_status`]: struct.Context.html#method.reset_status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetStatus {
+    /// The context wasn't reset.
+    NoError,
+    /// The context was reset because of something it did, e.g. an infinite
+    /// loop in a shader.
+    Guilty,
+    /// The context was reset because of something another context did.
+    Innocent,
+    /// The context was reset, but the driver doesn't know why.
+    Unknown,
+}
+
+impl ResetStatus {
+    pub(crate) fn from_gl(status: u32) -> Self {
+        match status {
+            0x8253 => ResetStatus::Guilty,
+            0x8254 => ResetStatus::Innocent,
+            0x8255 => ResetStatus::Unknown,
+            _ => ResetStatus::NoError,
+        }
+    }
+}
+
+/// A context which can be built again from the same [`ContextBuilder`] once
+/// it's lost, e.g. after a GPU reset.
+///
+/// Obtained from [`ContextBuilder::build_recoverable`]. The new context
+/// shares none of the objects of the lost one, so the application has to
+/// create them again, which it's told to by [`recover_if_lost`].
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(not(feature = "winit"))]
+/// # fn main() {}
+/// # #[cfg(feature = "winit")]
+/// # fn main() {
+/// # let el = glutin::event_loop::EventLoop::new();
+/// # use glutin::CreationError;
+/// # let size = glutin::dpi::PhysicalSize::new(800, 600);
+/// # fn upload_resources(_: &glutin::Context<glutin::PossiblyCurrent>) {}
+/// let mut recoverable = glutin::ContextBuilder::new()
+///     .with_gl_robustness(glutin::Robustness::TryRobustLoseContextOnReset)
+///     .build_recoverable(|cb| unsafe {
+///         let context = cb.build_headless(&el, size)?;
+///         context.make_current().map_err(|(_, err)| CreationError::OsError(err.to_string()))
+///     })
+///     .unwrap();
+/// upload_resources(recoverable.context());
+///
+/// // after rendering a frame
+/// if let Ok(status) = recoverable.context().reset_status() {
+///     if status != glutin::ResetStatus::NoError {
+///         recoverable.recover().unwrap();
+///         upload_resources(recoverable.context());
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`ContextBuilder`]: struct.ContextBuilder.html
+/// [`ContextBuilder::build_recoverable`]: struct.ContextBuilder.html#method.build_recoverable
+/// [`recover_if_lost`]: #method.recover_if_lost
+pub struct RecoverableContext<'a, T: ContextCurrentState, C, F> {
+    builder: ContextBuilder<'a, T>,
+    build: F,
+    context: C,
+    recoveries: u32,
+}
+
+impl<'a, T: ContextCurrentState, C: fmt::Debug, F> fmt::Debug for RecoverableContext<'a, T, C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecoverableContext")
+            .field("builder", &self.builder)
+            .field("context", &self.context)
+            .field("recoveries", &self.recoveries)
+            .finish()
+    }
+}
+
+impl<'a, T, C, F> RecoverableContext<'a, T, C, F>
+where
+    T: ContextCurrentState,
+    F: FnMut(ContextBuilder<'a, T>) -> Result<C, CreationError>,
+{
+    /// Returns the current context.
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Returns the current context.
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Returns the context, giving up on recovering it.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+
+    /// Returns how many times the context was built again.
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+
+    /// Builds the context again, replacing the current one, which is dropped
+    /// after the new one is built.
+    ///
+    /// On error, the current context is kept.
+    pub fn recover(&mut self) -> Result<&C, CreationError> {
+        self.context = (self.build)(self.builder.clone())?;
+        self.recoveries += 1;
+        Ok(&self.context)
+    }
+
+    /// Builds the context again if `err` tells it was lost, e.g. as returned
+    /// by `swap_buffers`.
+    ///
+    /// Returns whether it was, in which case the application must create its
+    /// objects again with the new context.
+    pub fn recover_if_lost(&mut self, err: &ContextError) -> Result<bool, CreationError> {
+        match *err {
+            ContextError::ContextLost => self.recover().map(|_| true),
+            _ => Ok(false),
+        }
+    }
+}
+
+impl<'a, T: ContextCurrentState> ContextBuilder<'a, T> {
+    /// Builds a context with `build`, keeping this builder to build it again
+    /// once it's lost. See [`RecoverableContext`].
+    ///
+    /// `build` can build any kind of context, and e.g. make it current. The
+    /// context it builds shares objects with the one set with
+    /// [`with_shared_lists`], if any, which is only useful if that one wasn't
+    /// lost too.
+    ///
+    /// [`RecoverableContext`]: struct.RecoverableContext.html
+    /// [`with_shared_lists`]: #method.with_shared_lists
+    pub fn build_recoverable<C, F>(
+        self,
+        mut build: F,
+    ) -> Result<RecoverableContext<'a, T, C, F>, CreationError>
+    where
+        F: FnMut(ContextBuilder<'a, T>) -> Result<C, CreationError>,
+    {
+        let context = build(self.clone())?;
+        Ok(RecoverableContext { builder: self, build, context, recoveries: 0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_status_from_gl() {
+        assert_eq!(ResetStatus::from_gl(0x8253), ResetStatus::Guilty);
+        assert_eq!(ResetStatus::from_gl(0x8254), ResetStatus::Innocent);
+        assert_eq!(ResetStatus::from_gl(0x8255), ResetStatus::Unknown);
+        assert_eq!(ResetStatus::from_gl(0), ResetStatus::NoError);
+        assert_eq!(ResetStatus::from_gl(0x8256), ResetStatus::NoError);
+    }
+}
@@ -1,7 +1,9 @@
 use super::*;
 
-use std::marker::PhantomData;
+use raw_window_handle::HasRawWindowHandle;
+#[cfg(feature = "winit")]
 use winit::event_loop::EventLoopWindowTarget;
+#[cfg(feature = "winit")]
 use winit::window::{Window, WindowBuilder};
 
 /// Represents an OpenGL [`Context`] and the [`Window`] with which it is
@@ -26,19 +28,25 @@ use winit::window::{Window, WindowBuilder};
 /// [`ContextWrapper<T, Window>`]: struct.ContextWrapper.html
 /// [`Window`]: struct.Window.html
 /// [`Context`]: struct.Context.html
+#[cfg(feature = "winit")]
 pub type WindowedContext<T> = ContextWrapper<T, Window>;
 
 /// Represents an OpenGL [`Context`] which has an underlying window that is
 /// stored separately.
 ///
-/// This type can only be created via one of three ways:
+/// This type can only be created via one of five ways:
 ///
 ///  * [`platform::unix::RawContextExt`]
 ///  * [`platform::windows::RawContextExt`]
+///  * [`ContextBuilder::build_from_raw_window_handle`]
 ///  * [`WindowedContext<T>::split`]
+///  * [`ContextBuilder::build_mock`], with the `mock` feature
 ///
 /// Please see [`ContextWrapper<T, ()>`].
 ///
+/// [`ContextBuilder::build_from_raw_window_handle`]:
+/// struct.ContextBuilder.html#method.build_from_raw_window_handle
+/// [`ContextBuilder::build_mock`]: struct.ContextBuilder.html#method.build_mock
 /// [`ContextWrapper<T, ()>`]: struct.ContextWrapper.html
 /// [`WindowedContext<T>::split`]: type.WindowedContext.html#method.split
 /// [`Context`]: struct.Context.html
@@ -95,6 +103,7 @@ pub struct ContextWrapper<T: ContextCurrentState, W> {
     pub(crate) window: W,
 }
 
+#[cfg(feature = "winit")]
 impl<T: ContextCurrentState> WindowedContext<T> {
     /// Borrow the inner `W`.
     pub fn window(&self) -> &Window {
@@ -133,6 +142,10 @@ impl<W> ContextWrapper<PossiblyCurrent, W> {
     /// Swaps the buffers in case of double or triple buffering using specified
     /// damage rects.
     ///
+    /// With GLX, the damaged regions are copied to the front buffer with
+    /// `GLX_MESA_copy_sub_buffer` instead, which leaves the back buffer
+    /// untouched and doesn't wait for vsync.
+    ///
     /// You should call this function every time you have finished rendering, or
     /// the image may not be displayed on the screen.
     ///
@@ -146,11 +159,47 @@ impl<W> ContextWrapper<PossiblyCurrent, W> {
 
     /// Returns whether or not swap_buffer_with_damage is available. If this
     /// function returns false, any call to swap_buffers_with_damage will
-    /// return an error.
+    /// return an error, except with GLX where it falls back to a full swap.
     pub fn swap_buffers_with_damage_supported(&self) -> bool {
         self.context.context.swap_buffers_with_damage_supported()
     }
 
+    /// Sets the region of the back buffer that the next frame will draw to,
+    /// using the same coordinates as [`swap_buffers_with_damage`].
+    ///
+    /// Must be called before drawing to the back buffer, at most once per
+    /// frame. Drivers are free to skip rendering outside of the region, and
+    /// the content there is undefined afterwards; see [`buffer_age`] to know
+    /// what has to be repainted.
+    ///
+    /// [`swap_buffers_with_damage`]: #method.swap_buffers_with_damage
+    /// [`buffer_age`]: #method.buffer_age
+    pub fn set_damage_region(&self, rects: &[Rect]) -> Result<(), ContextError> {
+        self.context.context.set_damage_region(rects)
+    }
+
+    /// Returns whether or not `set_damage_region` is available. If this
+    /// function returns false, any call to `set_damage_region` will fail with
+    /// [`ContextError::FunctionUnavailable`].
+    ///
+    /// [`ContextError::FunctionUnavailable`]: enum.ContextError.html#variant.FunctionUnavailable
+    pub fn set_damage_region_supported(&self) -> bool {
+        self.context.context.set_damage_region_supported()
+    }
+
+    /// Returns the age of the back buffer, that is how many swaps ago its
+    /// content was presented, or `Some(0)` if its content is undefined.
+    ///
+    /// Returns `None` if the backend can't tell, through `EGL_EXT_buffer_age`
+    /// or `GLX_EXT_buffer_age`. Everything has to be repainted then.
+    ///
+    /// See [`DamageHistory`] to work out what to repaint for a given age.
+    ///
+    /// [`DamageHistory`]: struct.DamageHistory.html
+    pub fn buffer_age(&self) -> Option<u32> {
+        self.context.context.buffer_age()
+    }
+
     /// Returns the pixel format of the main framebuffer of the context.
     pub fn get_pixel_format(&self) -> PixelFormat {
         self.context.context.get_pixel_format()
@@ -167,8 +216,7 @@ impl<W> ContextWrapper<PossiblyCurrent, W> {
     /// [`PhysicalSize`]: dpi/struct.PhysicalSize.html
     /// [`Resized`]: event/enum.WindowEvent.html#variant.Resized
     pub fn resize(&self, size: dpi::PhysicalSize<u32>) {
-        let (width, height) = size.into();
-        self.context.context.resize(width, height);
+        let _ = self.context.try_resize(size);
     }
 }
 
@@ -301,6 +349,18 @@ impl<T: ContextCurrentState, W> ContextWrapper<T, W> {
     pub fn get_api(&self) -> Api {
         self.context.get_api()
     }
+
+    /// Returns the version glutin requested when creating the context, if the
+    /// backend lets it pick one, e.g. the one chosen out of
+    /// [`GlRequest::Ranges`].
+    ///
+    /// Drivers may hand out a later, compatible version than the one
+    /// requested; query `GL_VERSION` for the actual one.
+    ///
+    /// [`GlRequest::Ranges`]: enum.GlRequest.html#variant.Ranges
+    pub fn get_api_version(&self) -> Option<(u8, u8)> {
+        self.context.get_api_version()
+    }
 }
 
 impl<W> ContextWrapper<PossiblyCurrent, W> {
@@ -331,15 +391,45 @@ impl<'a, T: ContextCurrentState> ContextBuilder<'a, T> {
     ///
     /// [`WindowedContext<T>`]: type.WindowedContext.html
     /// [`Context`]: struct.Context.html
+    #[cfg(feature = "winit")]
     pub fn build_windowed<TE>(
         self,
         wb: WindowBuilder,
         el: &EventLoopWindowTarget<TE>,
     ) -> Result<WindowedContext<NotCurrent>, CreationError> {
-        let ContextBuilder { pf_reqs, gl_attr } = self;
-        let gl_attr = gl_attr.map_sharing(|ctx| &ctx.context);
-        platform_impl::Context::new_windowed(wb, el, &pf_reqs, &gl_attr).map(|(window, context)| {
-            WindowedContext { window, context: Context { context, phantom: PhantomData } }
+        self.build_with(|pf_reqs, gl_attr| {
+            platform_impl::Context::new_windowed(wb, el, pf_reqs, gl_attr)
+        })
+        .map(|(window, context)| WindowedContext { window, context })
+    }
+
+    /// Builds a GL context for a window created by another library, e.g. SDL
+    /// or GTK, returning it as a [`RawContext<T>`].
+    ///
+    /// Wayland, Xlib and XCB windows are supported on Unix, Win32 windows on
+    /// Windows and native windows on Android. `size` is the size of the
+    /// window in physical pixels, only used on Wayland.
+    ///
+    /// XCB windows are handled like with
+    /// [`RawContextExt::build_raw_xcb_context`].
+    ///
+    /// # Safety
+    ///
+    /// The window must outlive the context, and its handle must be valid.
+    ///
+    /// [`RawContext<T>`]: type.RawContext.html
+    /// [`RawContextExt::build_raw_xcb_context`]:
+    /// platform/unix/trait.RawContextExt.html#tymethod.build_raw_xcb_context
+    pub unsafe fn build_from_raw_window_handle(
+        self,
+        window: &impl HasRawWindowHandle,
+        size: dpi::PhysicalSize<u32>,
+    ) -> Result<RawContext<NotCurrent>, CreationError> {
+        let handle = window.raw_window_handle();
+        self.build_with(|pf_reqs, gl_attr| {
+            platform_impl::Context::new_from_raw_window_handle(handle, size, pf_reqs, gl_attr)
+                .map(|context| ((), context))
         })
+        .map(|(window, context)| RawContext { window, context })
     }
 }
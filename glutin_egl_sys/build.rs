@@ -28,6 +28,8 @@ fn main() {
                 "EGL_KHR_create_context",
                 "EGL_EXT_create_context_robustness",
                 "EGL_KHR_create_context_no_error",
+                "EGL_IMG_context_priority",
+                "EGL_KHR_context_flush_control",
                 "EGL_KHR_platform_x11",
                 "EGL_KHR_platform_android",
                 "EGL_KHR_platform_wayland",
@@ -37,7 +39,11 @@ fn main() {
                 "EGL_MESA_platform_gbm",
                 "EGL_EXT_platform_wayland",
                 "EGL_EXT_platform_device",
+                "EGL_EXT_device_query",
+                "EGL_EXT_device_drm",
                 "EGL_KHR_swap_buffers_with_damage",
+                "EGL_EXT_buffer_age",
+                "EGL_KHR_partial_update",
             ],
         );
 
@@ -69,6 +69,9 @@ fn main() {
                     // composited to screen.
                     //
                     // Panics if damage is not supported due to the unwrap.
+                    //
+                    // With a buffer age, only the damage of the frames since
+                    // would need repainting; see `glutin::DamageHistory`.
                     color = color.next();
                     gl.draw_frame([color.red, color.green, color.blue, 1.0]);
                     if windowed_context.swap_buffers_with_damage_supported() {
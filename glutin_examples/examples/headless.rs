@@ -8,64 +8,29 @@ use glutin::{
 use std::path::Path;
 use support::gl;
 
-#[cfg(target_os = "linux")]
-fn build_context_surfaceless<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-    el: &EventLoop<()>,
-) -> Result<Context<NotCurrent>, CreationError> {
-    use glutin::platform::unix::HeadlessContextExt;
-    cb.build_surfaceless(&el)
-}
-
-fn build_context_headless<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-    el: &EventLoop<()>,
-) -> Result<Context<NotCurrent>, CreationError> {
-    let size_one = PhysicalSize::new(1, 1);
-    cb.build_headless(&el, size_one)
-}
-
-#[cfg(target_os = "linux")]
-fn build_context_osmesa<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-) -> Result<Context<NotCurrent>, CreationError> {
-    use glutin::platform::unix::HeadlessContextExt;
-    let size_one = PhysicalSize::new(1, 1);
-    cb.build_osmesa(size_one)
-}
-
 #[cfg(target_os = "linux")]
 fn build_context<T1: ContextCurrentState>(
     cb: ContextBuilder<T1>,
-) -> Result<(Context<NotCurrent>, EventLoop<()>), [CreationError; 3]> {
+) -> Result<(Context<NotCurrent>, EventLoop<()>), CreationError> {
+    use glutin::platform::unix::{HeadlessContextExt, OffscreenBackend};
+
     // On unix operating systems, you should always try for surfaceless first,
     // and if that does not work, headless (pbuffers), and if that too fails,
-    // finally osmesa.
+    // finally osmesa. `build_offscreen` does just that, unless overridden by
+    // the `GLUTIN_OFFSCREEN_BACKENDS` environment variable.
     //
     // If willing, you could attempt to use hidden windows instead of os mesa,
     // but note that you must handle events for the window that come on the
     // events loop.
     let el = EventLoop::new();
+    let size_one = PhysicalSize::new(1, 1);
+    let (ctx, report) = cb.build_offscreen(&el, size_one, OffscreenBackend::DEFAULT_ORDER)?;
+    for (backend, err) in report.failures {
+        println!("{:?} failed: {}", backend, err);
+    }
+    println!("Using {:?}", report.backend);
 
-    println!("Trying surfaceless");
-    let err1 = match build_context_surfaceless(cb.clone(), &el) {
-        Ok(ctx) => return Ok((ctx, el)),
-        Err(err) => err,
-    };
-
-    println!("Trying headless");
-    let err2 = match build_context_headless(cb.clone(), &el) {
-        Ok(ctx) => return Ok((ctx, el)),
-        Err(err) => err,
-    };
-
-    println!("Trying osmesa");
-    let err3 = match build_context_osmesa(cb) {
-        Ok(ctx) => return Ok((ctx, el)),
-        Err(err) => err,
-    };
-
-    Err([err1, err2, err3])
+    Ok((ctx, el))
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -73,7 +38,8 @@ fn build_context<T1: ContextCurrentState>(
     cb: ContextBuilder<T1>,
 ) -> Result<(Context<NotCurrent>, EventLoop<()>), CreationError> {
     let el = EventLoop::new();
-    build_context_headless(cb.clone(), &el).map(|ctx| (ctx, el))
+    let size_one = PhysicalSize::new(1, 1);
+    cb.build_headless(&el, size_one).map(|ctx| (ctx, el))
 }
 
 fn main() {
@@ -36,8 +36,11 @@ fn main() {
                 "GLX_EXT_framebuffer_sRGB",
                 "GLX_ARB_multisample",
                 "GLX_EXT_swap_control",
+                "GLX_EXT_buffer_age",
                 "GLX_SGI_swap_control",
                 "GLX_MESA_swap_control",
+                "GLX_MESA_copy_sub_buffer",
+                "GLX_MESA_query_renderer",
             ],
         )
         .write_bindings(gl_generator::StructGenerator, &mut file)
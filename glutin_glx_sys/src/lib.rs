@@ -8,6 +8,7 @@
 
 pub use self::glx::types::GLXContext;
 pub use x11_dl::xlib::*;
+pub use x11_dl::{error, xlib, xlib_xcb, xrender};
 
 /// GLX bindings
 pub mod glx {